@@ -0,0 +1,91 @@
+use derive_more::{Display, Error};
+use std::{collections::HashMap, convert::TryFrom, str::FromStr};
+
+/// Names of the attributes holding labels in formats with arbitrary attributes, such as GraphML
+/// and GML.
+///
+/// They default to `vlabel` and `elabel`, as NetworkX keeps node names in `label`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelAttributes {
+    pub vlabel: String,
+    pub elabel: String,
+}
+
+impl Default for LabelAttributes {
+    fn default() -> Self {
+        Self {
+            vlabel: "vlabel".to_string(),
+            elabel: "elabel".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(fmt = "{} distinct values are too many to be numbered", num_values)]
+pub struct TooManyValues {
+    pub num_values: usize,
+}
+
+/// Maps attribute values to numbers.
+///
+/// Values are parsed if they all are numbers, missing ones becoming 0.  Otherwise, the distinct
+/// values are numbered from 0 in order of first appearance, missing ones being the empty string.
+pub(crate) fn values_to_numbers<T>(values: &[Option<String>]) -> Result<Vec<T>, TooManyValues>
+where
+    T: FromStr + TryFrom<usize> + Default,
+{
+    let numbers: Option<Vec<T>> = values
+        .iter()
+        .map(|value| match value {
+            Some(value) => value.trim().parse().ok(),
+            None => Some(T::default()),
+        })
+        .collect();
+    if let Some(numbers) = numbers {
+        return Ok(numbers);
+    }
+    let mut dictionary = HashMap::new();
+    for value in values {
+        let len = dictionary.len();
+        dictionary
+            .entry(value.as_deref().unwrap_or(""))
+            .or_insert(len);
+    }
+    values
+        .iter()
+        .map(|value| {
+            T::try_from(dictionary[value.as_deref().unwrap_or("")]).map_err(|_| TooManyValues {
+                num_values: dictionary.len(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VLabel;
+
+    #[test]
+    fn test_values_to_numbers() {
+        let values = |values: &[Option<&str>]| -> Vec<Option<String>> {
+            values.iter().map(|value| value.map(String::from)).collect()
+        };
+        assert_eq!(
+            values_to_numbers::<VLabel>(&values(&[Some("3"), None, Some(" 7")])),
+            Ok(vec![3, 0, 7])
+        );
+        assert_eq!(
+            values_to_numbers::<VLabel>(&values(&[Some("b"), Some("3"), Some("b"), None])),
+            Ok(vec![0, 1, 0, 2])
+        );
+        assert_eq!(
+            values_to_numbers::<i8>(
+                &(0..200)
+                    .map(|i| Some(format!("v{}", i)))
+                    .collect::<Vec<_>>()
+            ),
+            Err(TooManyValues { num_values: 200 })
+        );
+    }
+}
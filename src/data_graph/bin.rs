@@ -0,0 +1,120 @@
+//! Binary data graph format.
+//!
+//! A binary data graph is a 32-byte header followed by the packed records:
+//!
+//! | offset | size                | content                                    |
+//! |--------|---------------------|--------------------------------------------|
+//! | 0      | 8                   | magic `b"OPGMBIN\0"`                       |
+//! | 8      | 4                   | version, `u32`                             |
+//! | 12     | 4                   | endianness mark `0x01020304`, `u32`        |
+//! | 16     | 8                   | number of vertices `n`, `u64`              |
+//! | 24     | 8                   | number of edges `m`, `u64`                 |
+//! | 32     | `n * 6`             | `n` records of `VIdVLabel`                 |
+//! | ...    | `m * 10`            | `m` records of `VIdVIdELabel`              |
+//!
+//! Numbers are in the byte order of the machine which wrote the file, and readers reject files
+//! whose endianness mark does not read back as `0x01020304`.
+
+use crate::types::{VIdVIdELabel, VIdVLabel};
+use std::{
+    convert::TryInto,
+    io::{Error, ErrorKind, Result, Write},
+    mem::size_of,
+};
+
+pub const MAGIC: &[u8; 8] = b"OPGMBIN\0";
+pub const VERSION: u32 = 1;
+pub const ENDIANNESS: u32 = 0x0102_0304;
+pub const HEADER_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BinHeader {
+    pub num_vertices: usize,
+    pub num_edges: usize,
+}
+
+impl BinHeader {
+    pub fn write<W: Write>(&self, buf: &mut W) -> Result<()> {
+        buf.write_all(MAGIC)?;
+        buf.write_all(&VERSION.to_ne_bytes())?;
+        buf.write_all(&ENDIANNESS.to_ne_bytes())?;
+        buf.write_all(&(self.num_vertices as u64).to_ne_bytes())?;
+        buf.write_all(&(self.num_edges as u64).to_ne_bytes())
+    }
+
+    /// Reads the header and checks the size of the file matches the counts.
+    pub fn read(bytes: &[u8]) -> Result<Self> {
+        let invalid = |message: String| Error::new(ErrorKind::InvalidData, message);
+        if bytes.len() < HEADER_SIZE || &bytes[0..8] != MAGIC {
+            return Err(invalid("not a binary data graph".to_string()));
+        }
+        let u32_at =
+            |offset: usize| u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_ne_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        if u32_at(12) != ENDIANNESS {
+            return Err(invalid(
+                "binary data graph of another endianness".to_string(),
+            ));
+        }
+        if u32_at(8) != VERSION {
+            return Err(invalid(format!(
+                "unsupported binary data graph version {}",
+                u32_at(8)
+            )));
+        }
+        let header = Self {
+            num_vertices: u64_at(16) as usize,
+            num_edges: u64_at(24) as usize,
+        };
+        match header.file_size() {
+            Some(file_size) if file_size == bytes.len() => Ok(header),
+            Some(file_size) => Err(invalid(format!(
+                "binary data graph of {} bytes instead of {}",
+                bytes.len(),
+                file_size
+            ))),
+            None => Err(invalid(
+                "binary data graph too large for this machine".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the size of the whole file, or `None` if it overflows `usize`.
+    pub fn file_size(&self) -> Option<usize> {
+        self.num_vertices
+            .checked_mul(size_of::<VIdVLabel>())?
+            .checked_add(self.num_edges.checked_mul(size_of::<VIdVIdELabel>())?)?
+            .checked_add(HEADER_SIZE)
+    }
+}
+
+pub fn write_vertex<W: Write>(buf: &mut W, vertex: VIdVLabel) -> Result<()> {
+    buf.write_all(unsafe {
+        std::slice::from_raw_parts(&vertex as *const _ as *const u8, size_of::<VIdVLabel>())
+    })
+}
+
+pub fn write_edge<W: Write>(buf: &mut W, edge: VIdVIdELabel) -> Result<()> {
+    buf.write_all(unsafe {
+        std::slice::from_raw_parts(&edge as *const _ as *const u8, size_of::<VIdVIdELabel>())
+    })
+}
+
+/// Returns the vertices and edges of a binary data graph.
+pub fn read_bin(bytes: &[u8]) -> Result<(&[VIdVLabel], &[VIdVIdELabel])> {
+    let header = BinHeader::read(bytes)?;
+    let edges_offset = HEADER_SIZE + header.num_vertices * size_of::<VIdVLabel>();
+    Ok(unsafe {
+        (
+            std::slice::from_raw_parts(
+                bytes[HEADER_SIZE..].as_ptr() as *const VIdVLabel,
+                header.num_vertices,
+            ),
+            std::slice::from_raw_parts(
+                bytes[edges_offset..].as_ptr() as *const VIdVIdELabel,
+                header.num_edges,
+            ),
+        )
+    })
+}
@@ -0,0 +1,122 @@
+use crate::data_graph::Graph;
+use std::collections::VecDeque;
+
+/// Order of the dense vertex ids.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VIdOrder {
+    /// Descending degree, ties broken by ascending `VId`.
+    Degree,
+    /// Breadth-first search ignoring directions, from the smallest unvisited `VId`.
+    Bfs,
+}
+
+/// Maps the vertex ids to `0..n`, storing the mapping in a `vid_map (vid, dense)` table.
+///
+/// The `vertices` and `edges` tables keep the original ids, see `use_dense_vids` to read the
+/// dense ones.
+pub fn compact_vids(conn: &sqlite::Connection, order: VIdOrder) -> sqlite::Result<usize> {
+    let graph = Graph::from_sqlite3(conn)?;
+    let degree = |i: usize| graph.out_arcs(i).len() + graph.in_arcs(i).len();
+    let indices: Vec<usize> = match order {
+        VIdOrder::Degree => {
+            let mut indices: Vec<usize> = (0..graph.num_vertices()).collect();
+            indices.sort_by_key(|&i| std::cmp::Reverse(degree(i)));
+            indices
+        }
+        VIdOrder::Bfs => {
+            let mut indices = Vec::with_capacity(graph.num_vertices());
+            let mut visited = vec![false; graph.num_vertices()];
+            let mut queue = VecDeque::new();
+            for root in 0..graph.num_vertices() {
+                if visited[root] {
+                    continue;
+                }
+                visited[root] = true;
+                queue.push_back(root);
+                while let Some(i) = queue.pop_front() {
+                    indices.push(i);
+                    for &(j, _) in graph.out_arcs(i).iter().chain(graph.in_arcs(i)) {
+                        if !visited[j] {
+                            visited[j] = true;
+                            queue.push_back(j);
+                        }
+                    }
+                }
+            }
+            indices
+        }
+    };
+    conn.execute("DROP TABLE IF EXISTS vid_map")?;
+    conn.execute("CREATE TABLE vid_map (vid INT PRIMARY KEY, dense INT)")?;
+    conn.execute("BEGIN")?;
+    let mut stat = conn.prepare("INSERT INTO vid_map VALUES (?, ?)")?;
+    for (dense, &i) in indices.iter().enumerate() {
+        stat.bind(1, graph.vid(i) as i64)?;
+        stat.bind(2, dense as i64)?;
+        stat.next()?;
+        stat.reset()?;
+    }
+    conn.execute("END")?;
+    Ok(indices.len())
+}
+
+/// Shadows the `vertices` and `edges` tables of `conn` by temporary views with the dense ids of
+/// `vid_map`, so that exporters write dense ids.
+pub fn use_dense_vids(conn: &sqlite::Connection) -> sqlite::Result<()> {
+    conn.execute(
+        "CREATE TEMP VIEW vertices AS \
+         SELECT m.dense AS vid, v.vlabel AS vlabel \
+         FROM main.vertices v JOIN main.vid_map m ON m.vid = v.vid",
+    )?;
+    conn.execute(
+        "CREATE TEMP VIEW edges AS \
+         SELECT s.dense AS src, d.dense AS dst, e.elabel AS elabel \
+         FROM main.edges e \
+         JOIN main.vid_map s ON s.vid = e.src \
+         JOIN main.vid_map d ON d.vid = e.dst",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{sqlite3_to_graphflow, write_sqlite3};
+
+    #[test]
+    fn test_compact_vids() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(10, 1), (20, 2), (30, 3), (40, 4)],
+            vec![(10, 30, 0), (30, 20, 5), (40, 30, 0)],
+        )
+        .unwrap();
+        let vid_map = || {
+            let mut stat = conn
+                .prepare("SELECT vid FROM vid_map ORDER BY dense")
+                .unwrap();
+            let mut vids = Vec::new();
+            while let sqlite::State::Row = stat.next().unwrap() {
+                vids.push(stat.read::<i64>(0).unwrap());
+            }
+            vids
+        };
+        assert_eq!(compact_vids(&conn, VIdOrder::Bfs).unwrap(), 4);
+        assert_eq!(vid_map(), vec![10, 30, 20, 40]);
+        assert_eq!(compact_vids(&conn, VIdOrder::Degree).unwrap(), 4);
+        assert_eq!(vid_map(), vec![30, 10, 20, 40]);
+        use_dense_vids(&conn).unwrap();
+        let (mut vertices_buf, mut edges_buf) = (Vec::new(), Vec::new());
+        assert_eq!(
+            sqlite3_to_graphflow(&conn, &mut vertices_buf, &mut edges_buf).unwrap(),
+            (4, 3)
+        );
+        assert_eq!(
+            String::from_utf8(vertices_buf).unwrap(),
+            "0,3\n1,1\n2,2\n3,4\n"
+        );
+        let mut edges: Vec<_> = std::str::from_utf8(&edges_buf).unwrap().lines().collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec!["0,2,6", "1,0,1", "3,0,1"]);
+    }
+}
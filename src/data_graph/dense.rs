@@ -0,0 +1,107 @@
+//! Arrays indexed by `VId`, for formats without vertex ids.
+
+use crate::types::VLabel;
+use memmap::MmapMut;
+use std::{fs::File, mem::size_of};
+
+/// Returns the number of slots of arrays indexed by `VId`, i.e. the largest `VId` plus one.
+///
+/// Fails if a `VId` is negative, as it has no slot.
+pub(crate) fn select_num_slots(conn: &sqlite::Connection) -> sqlite::Result<usize> {
+    let mut stat =
+        conn.prepare("SELECT COALESCE(MIN(vid), 0), COALESCE(MAX(vid) + 1, 0) FROM vertices")?;
+    stat.next()?;
+    slot(stat.read::<i64>(0)?)?;
+    Ok(stat.read::<i64>(1)? as usize)
+}
+
+/// Returns the slot of a `VId`, failing if it is negative.
+fn slot(vid: i64) -> sqlite::Result<usize> {
+    if vid < 0 {
+        return Err(sqlite::Error {
+            code: None,
+            message: Some(format!("vertex {} has a negative id", vid)),
+        });
+    }
+    Ok(vid as usize)
+}
+
+/// Memory-mapped array of `len` values of `T` backed by `file`, which must be readable and
+/// writable.  The file is resized and zeroed.
+pub(crate) struct MmapArray<T> {
+    mmap: Option<MmapMut>,
+    len: usize,
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy> MmapArray<T> {
+    pub(crate) fn new(file: &File, len: usize) -> std::io::Result<Self> {
+        file.set_len(0)?;
+        file.set_len((len * size_of::<T>()) as u64)?;
+        Ok(Self {
+            // Empty files cannot be mapped.
+            mmap: match len {
+                0 => None,
+                _ => Some(unsafe { MmapMut::map_mut(file)? }),
+            },
+            len,
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.mmap {
+            Some(mmap) => unsafe {
+                std::slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut T, self.len)
+            },
+            None => &mut [],
+        }
+    }
+
+    pub(crate) fn flush(&self) -> std::io::Result<()> {
+        match &self.mmap {
+            Some(mmap) => mmap.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads the vertex labels into an array indexed by `VId`, leaving 0 for missing vertices.
+///
+/// Fails if a `VId` is negative or has no slot in `vlabels`.
+pub(crate) fn read_vlabels(
+    conn: &sqlite::Connection,
+    vlabels: &mut [VLabel],
+) -> sqlite::Result<()> {
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        let vid = stat.read::<i64>(0)?;
+        let num_slots = vlabels.len();
+        let vlabel = vlabels.get_mut(slot(vid)?).ok_or_else(|| sqlite::Error {
+            code: None,
+            message: Some(format!("vertex {} is out of {} slots", vid, num_slots)),
+        })?;
+        *vlabel = stat.read::<i64>(1)? as VLabel;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::write_sqlite3;
+
+    #[test]
+    fn test_negative_vid() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(-5, 1), (-2, 2)], vec![]).unwrap();
+        assert!(select_num_slots(&conn).is_err());
+        assert!(read_vlabels(&conn, &mut [0; 4]).is_err());
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(0, 1), (2, 2)], vec![]).unwrap();
+        assert_eq!(select_num_slots(&conn).unwrap(), 3);
+        let mut vlabels = [0; 3];
+        read_vlabels(&conn, &mut vlabels).unwrap();
+        assert_eq!(vlabels, [1, 0, 2]);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::types::{ELabel, VId};
+use derive_more::{Display, Error};
+use std::io::BufRead;
+
+/// Options of edge list text files such as SNAP ones.
+///
+/// Columns are numbered from 0, the first two being the source and the destination.  Columns
+/// which are not used are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeListOptions {
+    /// Splits columns on whitespace if `None`.
+    pub delimiter: Option<char>,
+    /// Lines starting with it are skipped.
+    pub comment: String,
+    /// Number of lines to skip at the beginning of the file.
+    pub skip_header: usize,
+    /// Column of edge labels, every edge having label 0 if `None`.
+    pub elabel_column: Option<usize>,
+    /// Column of weights, which are checked and returned but not stored in SQLite3.
+    pub weight_column: Option<usize>,
+    /// Fails with the numbers of the rejected lines instead of skipping them.
+    pub strict: bool,
+}
+
+impl Default for EdgeListOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            comment: "#".to_string(),
+            skip_header: 0,
+            elabel_column: None,
+            weight_column: None,
+            strict: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    pub src: VId,
+    pub dst: VId,
+    pub elabel: ELabel,
+    pub weight: Option<f64>,
+}
+
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(
+    fmt = "{} lines rejected, starting with lines {:?}",
+    "line_numbers.len()",
+    "&line_numbers[..line_numbers.len().min(10)]"
+)]
+pub struct RejectedLines {
+    pub line_numbers: Vec<usize>,
+}
+
+/// Iterates over the edges of an edge list, skipping the lines which cannot be parsed.
+pub struct EdgeListReader<'a, R> {
+    lines: std::io::Lines<R>,
+    options: &'a EdgeListOptions,
+    line_number: usize,
+    rejected: Vec<usize>,
+}
+
+impl<'a, R: BufRead> EdgeListReader<'a, R> {
+    pub fn new(reader: R, options: &'a EdgeListOptions) -> Self {
+        Self {
+            lines: reader.lines(),
+            options,
+            line_number: 0,
+            rejected: Vec::new(),
+        }
+    }
+
+    /// Returns the numbers, starting from 1, of the lines rejected so far.
+    pub fn rejected(&self) -> &[usize] {
+        &self.rejected
+    }
+
+    /// Fails with the rejected lines in strict mode.
+    pub fn finish(self) -> Result<(), RejectedLines> {
+        if self.options.strict && !self.rejected.is_empty() {
+            Err(RejectedLines {
+                line_numbers: self.rejected,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_line(&self, line: &str) -> Option<Edge> {
+        let columns: Vec<&str> = match self.options.delimiter {
+            Some(delimiter) => line.split(delimiter).map(|column| column.trim()).collect(),
+            None => line.split_whitespace().collect(),
+        };
+        Some(Edge {
+            src: columns.first()?.parse().ok()?,
+            dst: columns.get(1)?.parse().ok()?,
+            elabel: match self.options.elabel_column {
+                Some(i) => columns.get(i)?.parse().ok()?,
+                None => 0,
+            },
+            weight: match self.options.weight_column {
+                Some(i) => Some(columns.get(i)?.parse().ok()?),
+                None => None,
+            },
+        })
+    }
+}
+
+impl<'a, R: BufRead> Iterator for EdgeListReader<'a, R> {
+    type Item = std::io::Result<Edge>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line_number += 1;
+            let line = line.trim();
+            if self.line_number <= self.options.skip_header
+                || line.is_empty()
+                || (!self.options.comment.is_empty() && line.starts_with(&self.options.comment))
+            {
+                continue;
+            }
+            match self.parse_line(line) {
+                Some(edge) => return Some(Ok(edge)),
+                None => self.rejected.push(self.line_number),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_list_reader() {
+        let text = "src,dst,label,weight\n% comment\n1,2,3,0.5\n\n2 ,3,4,1\n3,x,5,1\n4,5\n5,6,7,8";
+        let options = EdgeListOptions {
+            delimiter: Some(','),
+            comment: "%".to_string(),
+            skip_header: 1,
+            elabel_column: Some(2),
+            weight_column: Some(3),
+            strict: true,
+        };
+        let mut reader = EdgeListReader::new(text.as_bytes(), &options);
+        let edges: Vec<Edge> = reader.by_ref().map(|edge| edge.unwrap()).collect();
+        assert_eq!(
+            edges
+                .iter()
+                .map(|edge| (edge.src, edge.dst, edge.elabel, edge.weight))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 2, 3, Some(0.5)),
+                (2, 3, 4, Some(1.0)),
+                (5, 6, 7, Some(8.0))
+            ]
+        );
+        assert_eq!(reader.rejected(), &[6, 7]);
+        assert_eq!(
+            reader.finish(),
+            Err(RejectedLines {
+                line_numbers: vec![6, 7]
+            })
+        );
+
+        let text = "# FromNodeId\tToNodeId\n1\t2\n3 4 extra\n";
+        let options = EdgeListOptions::default();
+        let mut reader = EdgeListReader::new(text.as_bytes(), &options);
+        assert_eq!(reader.by_ref().count(), 2);
+        assert_eq!(reader.finish(), Ok(()));
+    }
+}
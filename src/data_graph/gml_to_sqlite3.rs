@@ -0,0 +1,205 @@
+use crate::{
+    data_graph::{attributes::values_to_numbers, write_sqlite3, LabelAttributes},
+    types::{ELabel, VId, VLabel},
+};
+use derive_more::Display;
+use pest::{iterators::Pair, Parser as PestParser};
+use pest_derive::Parser;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, Read},
+};
+
+#[derive(Parser)]
+#[grammar = "data_graph/gml.pest"]
+struct Parser;
+
+#[derive(Debug, Display, PartialEq)]
+pub enum GmlError {
+    #[display(fmt = "{}", _0)]
+    Syntax(Box<pest::error::Error<Rule>>),
+    #[display(fmt = "no graph")]
+    NoGraph,
+    #[display(fmt = "{} without `{}`", _0, _1)]
+    MissingKey(&'static str, &'static str),
+    #[display(fmt = "`{}` is not a vertex id", _0)]
+    InvalidId(String),
+    #[display(fmt = "node {} is declared more than once", _0)]
+    DuplicateNode(VId),
+    #[display(fmt = "node {} is not declared", _0)]
+    DanglingNode(VId),
+}
+
+impl std::error::Error for GmlError {}
+
+impl From<pest::error::Error<Rule>> for GmlError {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        GmlError::Syntax(Box::new(error))
+    }
+}
+
+/// Imports the first graph of a GML file, such as those written by NetworkX or Gephi.
+///
+/// Labels are read from the keys named by `attributes`, and kept if they all are numbers,
+/// otherwise numbered in order of appearance.  Missing labels are 0 or the empty string.  Edges of
+/// graphs without `directed 1` are imported as two opposite arcs.
+pub fn gml_to_sqlite3(
+    conn: &sqlite::Connection,
+    gml: &File,
+    attributes: &LabelAttributes,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut text = String::new();
+    BufReader::new(gml).read_to_string(&mut text)?;
+    let (vertices, edges) = read_gml(&text, attributes)?;
+    Ok(write_sqlite3(conn, vertices, edges)?)
+}
+
+#[allow(clippy::type_complexity)]
+fn read_gml(
+    text: &str,
+    attributes: &LabelAttributes,
+) -> Result<(Vec<(VId, VLabel)>, Vec<(VId, VId, ELabel)>), Box<dyn std::error::Error>> {
+    let gml = Parser::parse(Rule::Gml, text)
+        .map_err(GmlError::from)?
+        .next()
+        .unwrap();
+    let graph = gml
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::Pair)
+        .map(key_value)
+        .find(|(key, value)| *key == "graph" && value.as_rule() == Rule::List)
+        .ok_or(GmlError::NoGraph)?
+        .1;
+    let (mut vids, mut vlabels) = (vec![], vec![]);
+    let (mut arcs, mut elabels) = (vec![], vec![]);
+    let mut declared = HashSet::new();
+    let directed = graph
+        .clone()
+        .into_inner()
+        .map(key_value)
+        .any(|(key, value)| key == "directed" && value.as_str() == "1");
+    for (key, value) in graph.into_inner().map(key_value) {
+        match (key, value.as_rule()) {
+            ("node", Rule::List) => {
+                let mut entries: Vec<_> = value.into_inner().map(key_value).collect();
+                let vid = take_id(&mut entries, "node", "id")?;
+                if !declared.insert(vid) {
+                    return Err(Box::new(GmlError::DuplicateNode(vid)));
+                }
+                vids.push(vid);
+                vlabels.push(take_text(&mut entries, &attributes.vlabel));
+            }
+            ("edge", Rule::List) => {
+                let mut entries: Vec<_> = value.into_inner().map(key_value).collect();
+                arcs.push((
+                    take_id(&mut entries, "edge", "source")?,
+                    take_id(&mut entries, "edge", "target")?,
+                ));
+                elabels.push(take_text(&mut entries, &attributes.elabel));
+            }
+            _ => (),
+        }
+    }
+    for &(src, dst) in &arcs {
+        for vid in [src, dst].iter() {
+            if !declared.contains(vid) {
+                return Err(Box::new(GmlError::DanglingNode(*vid)));
+            }
+        }
+    }
+    let vlabels: Vec<VLabel> = values_to_numbers(&vlabels)?;
+    let elabels: Vec<ELabel> = values_to_numbers(&elabels)?;
+    let mut edges = Vec::with_capacity(arcs.len());
+    for ((src, dst), elabel) in arcs.into_iter().zip(elabels) {
+        edges.push((src, dst, elabel));
+        if !directed && src != dst {
+            edges.push((dst, src, elabel));
+        }
+    }
+    Ok((vids.into_iter().zip(vlabels).collect(), edges))
+}
+
+fn key_value(pair: Pair<'_, Rule>) -> (&str, Pair<'_, Rule>) {
+    let mut pairs = pair.into_inner();
+    let key = pairs.next().unwrap().as_str();
+    (key, pairs.next().unwrap())
+}
+
+fn take<'a>(entries: &mut Vec<(&str, Pair<'a, Rule>)>, key: &str) -> Option<Pair<'a, Rule>> {
+    let i = entries.iter().position(|(k, _)| *k == key)?;
+    Some(entries.remove(i).1)
+}
+
+fn take_id(
+    entries: &mut Vec<(&str, Pair<Rule>)>,
+    tag: &'static str,
+    key: &'static str,
+) -> Result<VId, GmlError> {
+    let value = take(entries, key).ok_or(GmlError::MissingKey(tag, key))?;
+    value
+        .as_str()
+        .parse()
+        .map_err(|_| GmlError::InvalidId(value.as_str().to_string()))
+}
+
+/// Returns the text of a number or string, without quotes.
+fn take_text(entries: &mut Vec<(&str, Pair<Rule>)>, key: &str) -> Option<String> {
+    let value = take(entries, key)?;
+    match value.as_rule() {
+        Rule::Str => Some(value.into_inner().next().unwrap().as_str().to_string()),
+        Rule::List => None,
+        _ => Some(value.as_str().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_gml() {
+        // As written by NetworkX, where `label` holds the node names.
+        let gml = r#"graph [
+  directed 1
+  node [
+    id 0
+    label "alice"
+    kind "person"
+  ]
+  node [
+    id 1
+    label "acme"
+    kind "company"
+    graphics [ x 1.5 y -2.0 ]
+  ]
+  edge [
+    source 0
+    target 1
+    type 3
+  ]
+]"#;
+        let attributes = LabelAttributes {
+            vlabel: "kind".to_string(),
+            elabel: "type".to_string(),
+        };
+        assert_eq!(
+            read_gml(gml, &attributes).unwrap(),
+            (vec![(0, 0), (1, 1)], vec![(0, 1, 3)])
+        );
+        assert_eq!(
+            read_gml("graph [ node [ id 0 ] edge [ source 0 ] ]", &attributes)
+                .unwrap_err()
+                .to_string(),
+            "edge without `target`"
+        );
+        assert_eq!(
+            read_gml(
+                "graph [ node [ id 0 ] node [ id 1 ] edge [ source 0 target 1 type 2 ] ]",
+                &attributes
+            )
+            .unwrap(),
+            (vec![(0, 0), (1, 0)], vec![(0, 1, 2), (1, 0, 2)])
+        );
+    }
+}
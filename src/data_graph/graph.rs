@@ -0,0 +1,80 @@
+use crate::types::{ELabel, VId, VLabel};
+use std::collections::HashMap;
+
+/// In-memory data graph, with vertices indexed by `0..num_vertices` in ascending order of `VId`.
+#[derive(Debug)]
+pub struct Graph {
+    vertices: Vec<(VId, VLabel)>,
+    indices: HashMap<VId, usize>,
+    out_arcs: Vec<Vec<(usize, ELabel)>>,
+    in_arcs: Vec<Vec<(usize, ELabel)>>,
+}
+
+impl Graph {
+    /// Fails if an edge references a vertex missing from the `vertices` table.
+    pub fn from_sqlite3(conn: &sqlite::Connection) -> sqlite::Result<Self> {
+        let mut vertices = Vec::new();
+        let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices ORDER BY vid")?;
+        while let sqlite::State::Row = stat.next()? {
+            vertices.push((stat.read::<i64>(0)? as VId, stat.read::<i64>(1)? as VLabel));
+        }
+        let indices: HashMap<VId, usize> = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, &(vid, _))| (vid, i))
+            .collect();
+        let mut out_arcs = vec![Vec::new(); vertices.len()];
+        let mut in_arcs = vec![Vec::new(); vertices.len()];
+        let index = |vid: i64| {
+            indices
+                .get(&(vid as VId))
+                .cloned()
+                .ok_or_else(|| sqlite::Error {
+                    code: None,
+                    message: Some(format!("vertex {} of an edge is not in vertices", vid)),
+                })
+        };
+        let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+        while let sqlite::State::Row = stat.next()? {
+            let src = index(stat.read::<i64>(0)?)?;
+            let dst = index(stat.read::<i64>(1)?)?;
+            let elabel = stat.read::<i64>(2)? as ELabel;
+            out_arcs[src].push((dst, elabel));
+            in_arcs[dst].push((src, elabel));
+        }
+        out_arcs.iter_mut().for_each(|arcs| arcs.sort_unstable());
+        in_arcs.iter_mut().for_each(|arcs| arcs.sort_unstable());
+        Ok(Self {
+            vertices,
+            indices,
+            out_arcs,
+            in_arcs,
+        })
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn vid(&self, i: usize) -> VId {
+        self.vertices[i].0
+    }
+
+    pub fn vlabel(&self, i: usize) -> VLabel {
+        self.vertices[i].1
+    }
+
+    pub fn index(&self, vid: VId) -> Option<usize> {
+        self.indices.get(&vid).cloned()
+    }
+
+    /// Returns `(dst, elabel)` of the arcs from `i`, sorted.
+    pub fn out_arcs(&self, i: usize) -> &[(usize, ELabel)] {
+        &self.out_arcs[i]
+    }
+
+    /// Returns `(src, elabel)` of the arcs to `i`, sorted.
+    pub fn in_arcs(&self, i: usize) -> &[(usize, ELabel)] {
+        &self.in_arcs[i]
+    }
+}
@@ -0,0 +1,177 @@
+//! Graph format of subgraph matching benchmarks, as used by CFL-Match, DAF or RapidMatch.
+//!
+//! ```text
+//! t 3 2
+//! v 0 1 1
+//! v 1 2 2
+//! v 2 1 1
+//! e 0 1 0
+//! e 1 2 0
+//! ```
+//!
+//! The `t` line gives the numbers of vertices and edges, each `v` line the id, label and degree
+//! of a vertex, and each `e` line the source, destination and label of an edge.  Vertex ids are
+//! `0..n`, edges are undirected and listed once, and the edge label may be omitted.
+//!
+//! Readers ignore the degrees and accept any vertex ids, while writers renumber the vertices.
+//! SQLite3 data graphs have directed arcs, so `graph_to_sqlite3` stores each edge as two opposite
+//! arcs, and `sqlite3_to_graph` writes each pair of opposite arcs as one edge.
+
+use crate::types::{ELabel, VId, VLabel};
+use derive_more::{Display, Error};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+#[derive(Debug, Display, Error)]
+pub enum GraphFormatError {
+    #[display(fmt = "line {}: cannot parse `{}`", _0, _1)]
+    Syntax(usize, #[error(not(source))] String),
+    #[display(fmt = "line {}: vertex {} is declared more than once", _0, _1)]
+    DuplicateVertex(usize, #[error(not(source))] VId),
+    #[display(fmt = "line {}: vertex {} is not declared", _0, _1)]
+    DanglingVertex(usize, #[error(not(source))] VId),
+    #[display(
+        fmt = "`t {} {}` does not match the {} vertices and {} edges",
+        _0,
+        _1,
+        _2,
+        _3
+    )]
+    CountMismatch(
+        #[error(not(source))] usize,
+        #[error(not(source))] usize,
+        #[error(not(source))] usize,
+        #[error(not(source))] usize,
+    ),
+    #[display(fmt = "vertex {} has a negative id, which gisp cannot express", _0)]
+    NegativeVertex(#[error(not(source))] VId),
+    #[display(fmt = "{}", _0)]
+    Io(std::io::Error),
+}
+
+/// Returns the vertices and edges of a graph, in the order of the file.
+#[allow(clippy::type_complexity)]
+pub fn read_graph<R: BufRead>(
+    reader: R,
+) -> Result<(Vec<(VId, VLabel)>, Vec<(VId, VId, ELabel)>), GraphFormatError> {
+    let (mut vertices, mut edges) = (vec![], vec![]);
+    let mut vids = HashMap::new();
+    let mut header = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(GraphFormatError::Io)?;
+        let syntax = || GraphFormatError::Syntax(i + 1, line.clone());
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        match columns.first() {
+            None => (),
+            Some(&"t") if header.is_none() && vertices.is_empty() && edges.is_empty() => {
+                header = Some(
+                    match (parse_column(&columns, 1), parse_column(&columns, 2)) {
+                        (Some(num_vertices), Some(num_edges)) => Some((num_vertices, num_edges)),
+                        // `t # 0` in older files.
+                        _ => None,
+                    },
+                );
+            }
+            Some(&"v") => {
+                let vid = parse_column(&columns, 1).ok_or_else(syntax)?;
+                let vlabel = parse_column(&columns, 2).ok_or_else(syntax)?;
+                if vids.insert(vid, vlabel).is_some() {
+                    return Err(GraphFormatError::DuplicateVertex(i + 1, vid));
+                }
+                vertices.push((vid, vlabel));
+            }
+            Some(&"e") => {
+                let src = parse_column(&columns, 1).ok_or_else(syntax)?;
+                let dst = parse_column(&columns, 2).ok_or_else(syntax)?;
+                let elabel = match columns.get(3) {
+                    Some(elabel) => elabel.parse().map_err(|_| syntax())?,
+                    None => 0,
+                };
+                for &vid in &[src, dst] {
+                    if !vids.contains_key(&vid) {
+                        return Err(GraphFormatError::DanglingVertex(i + 1, vid));
+                    }
+                }
+                edges.push((src, dst, elabel));
+            }
+            _ => return Err(syntax()),
+        }
+    }
+    match header {
+        Some(Some((num_vertices, num_edges)))
+            if (num_vertices, num_edges) != (vertices.len(), edges.len()) =>
+        {
+            Err(GraphFormatError::CountMismatch(
+                num_vertices,
+                num_edges,
+                vertices.len(),
+                edges.len(),
+            ))
+        }
+        _ => Ok((vertices, edges)),
+    }
+}
+
+fn parse_column<T: std::str::FromStr>(columns: &[&str], i: usize) -> Option<T> {
+    columns.get(i)?.parse().ok()
+}
+
+/// Writes a graph, renumbering the vertices by their positions in `vertices`.
+pub fn write_graph<W: Write>(
+    buf: &mut W,
+    vertices: &[(VId, VLabel)],
+    edges: &[(VId, VId, ELabel)],
+) -> std::io::Result<()> {
+    let indices: HashMap<VId, usize> = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, &(vid, _))| (vid, i))
+        .collect();
+    let mut degrees = vec![0; vertices.len()];
+    for &(src, dst, _) in edges {
+        degrees[indices[&src]] += 1;
+        degrees[indices[&dst]] += 1;
+    }
+    writeln!(buf, "t {} {}", vertices.len(), edges.len())?;
+    for (i, &(_, vlabel)) in vertices.iter().enumerate() {
+        writeln!(buf, "v {} {} {}", i, vlabel, degrees[i])?;
+    }
+    for &(src, dst, elabel) in edges {
+        writeln!(buf, "e {} {} {}", indices[&src], indices[&dst], elabel)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_format() {
+        let text = "t 3 2\nv 0 1 1\nv 1 2 2\nv 2 1 1\ne 0 1\ne 1 2 3\n";
+        let (vertices, edges) = read_graph(text.as_bytes()).unwrap();
+        assert_eq!(vertices, vec![(0, 1), (1, 2), (2, 1)]);
+        assert_eq!(edges, vec![(0, 1, 0), (1, 2, 3)]);
+        let mut buf = Vec::new();
+        write_graph(&mut buf, &[(5, 1), (7, 2)], &[(7, 5, 4)]).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "t 2 1\nv 0 1 1\nv 1 2 1\ne 1 0 4\n"
+        );
+
+        assert!(matches!(
+            read_graph("t 1 0\nv 0 1 0\ne 0 1\n".as_bytes()),
+            Err(GraphFormatError::DanglingVertex(3, 1))
+        ));
+        assert!(matches!(
+            read_graph("t 2 0\nv 0 1 0\n".as_bytes()),
+            Err(GraphFormatError::CountMismatch(2, 0, 1, 0))
+        ));
+        assert!(matches!(
+            read_graph("t # 0\nv 0 x 0\n".as_bytes()),
+            Err(GraphFormatError::Syntax(2, _))
+        ));
+    }
+}
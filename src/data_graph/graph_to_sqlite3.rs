@@ -0,0 +1,25 @@
+use crate::data_graph::{graph_format::read_graph, write_sqlite3};
+use std::{fs::File, io::BufReader};
+
+/// Imports a graph of subgraph matching benchmarks, see `data_graph::graph_format`.
+///
+/// Every undirected edge is stored as two opposite arcs, except self-loops which are stored once,
+/// so the number of arcs is returned.
+pub fn graph_to_sqlite3(
+    conn: &sqlite::Connection,
+    graph: &File,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let (vertices, edges) = read_graph(BufReader::new(graph))?;
+    Ok(write_sqlite3(
+        conn,
+        vertices,
+        edges.into_iter().flat_map(|(src, dst, elabel)| {
+            let reverse = if src == dst {
+                None
+            } else {
+                Some((dst, src, elabel))
+            };
+            std::iter::once((src, dst, elabel)).chain(reverse)
+        }),
+    )?)
+}
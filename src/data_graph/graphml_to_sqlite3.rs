@@ -0,0 +1,256 @@
+use crate::{
+    data_graph::{attributes::values_to_numbers, write_sqlite3, LabelAttributes},
+    types::{ELabel, VId, VLabel},
+};
+use derive_more::{Display, Error};
+use quick_xml::{events::Event, Reader};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum GraphmlError {
+    #[display(fmt = "<{}> without `{}` attribute", _0, _1)]
+    MissingAttribute(
+        #[error(not(source))] &'static str,
+        #[error(not(source))] &'static str,
+    ),
+    #[display(fmt = "node `{}` is declared more than once", _0)]
+    DuplicateNode(#[error(not(source))] String),
+    #[display(fmt = "node `{}` is not declared", _0)]
+    DanglingNode(#[error(not(source))] String),
+}
+
+/// Imports a GraphML file, such as those written by NetworkX or Gephi.
+///
+/// Node ids are kept if they all are numbers, otherwise nodes are numbered in order of
+/// appearance.  Labels are read from the `data` of the keys named by `attributes`, or their
+/// `default`, and mapped the same way.  Undirected edges, following `edgedefault` unless they
+/// have a `directed` attribute, are imported as two opposite arcs.  Nested graphs, hyperedges and
+/// ports are not supported.
+pub fn graphml_to_sqlite3(
+    conn: &sqlite::Connection,
+    graphml: &File,
+    attributes: &LabelAttributes,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let (vertices, edges) = read_graphml(BufReader::new(graphml), attributes)?;
+    Ok(write_sqlite3(conn, vertices, edges)?)
+}
+
+#[derive(Default)]
+struct Key {
+    id: String,
+    default: Option<String>,
+}
+
+enum Element {
+    Node,
+    Edge,
+}
+
+#[allow(clippy::type_complexity)]
+fn read_graphml<R: BufRead>(
+    reader: R,
+    attributes: &LabelAttributes,
+) -> Result<(Vec<(VId, VLabel)>, Vec<(VId, VId, ELabel)>), Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+    let (mut vkey, mut ekey) = (Key::default(), Key::default());
+    let (mut nodes, mut vlabels) = (vec![], vec![]);
+    let (mut arcs, mut elabels) = (vec![], vec![]);
+    let mut indices = HashMap::new();
+    let mut directed = true;
+    // The element being read and the key of the `data` being read.
+    let (mut element, mut text_key) = (None, None);
+    // Whether the key, and the `default` being read, are the ones of the vertex and of the edge
+    // labels, as a key `for="all"` may be both.
+    let (mut key_of, mut default_of) = ((false, false), (false, false));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf)? {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let mut attrs = HashMap::new();
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    attrs.insert(attr.key.to_vec(), attr.unescape_and_decode_value(&reader)?);
+                }
+                let mut get = |tag: &'static str, name: &'static str| {
+                    attrs
+                        .remove(name.as_bytes())
+                        .ok_or(GraphmlError::MissingAttribute(tag, name))
+                };
+                match e.local_name() {
+                    b"key" => {
+                        let id = get("key", "id")?;
+                        let name = get("key", "attr.name").unwrap_or_else(|_| id.clone());
+                        let domain = get("key", "for").unwrap_or_else(|_| "all".to_string());
+                        key_of = (
+                            (domain == "node" || domain == "all") && name == attributes.vlabel,
+                            (domain == "edge" || domain == "all") && name == attributes.elabel,
+                        );
+                        if key_of.0 {
+                            vkey.id = id.clone();
+                        }
+                        if key_of.1 {
+                            ekey.id = id;
+                        }
+                    }
+                    b"graph" => {
+                        directed = get("graph", "edgedefault")
+                            .map_or(true, |edgedefault| edgedefault != "undirected");
+                    }
+                    b"node" => {
+                        let id = get("node", "id")?;
+                        if indices.insert(id.clone(), nodes.len()).is_some() {
+                            return Err(Box::new(GraphmlError::DuplicateNode(id)));
+                        }
+                        nodes.push(Some(id));
+                        vlabels.push(None);
+                        element = Some(Element::Node);
+                    }
+                    b"edge" => {
+                        let directed =
+                            get("edge", "directed").map_or(directed, |directed| directed == "true");
+                        arcs.push((get("edge", "source")?, get("edge", "target")?, directed));
+                        elabels.push(None);
+                        element = Some(Element::Edge);
+                    }
+                    b"data" => {
+                        let key = get("data", "key")?;
+                        text_key = match element {
+                            Some(Element::Node) if key == vkey.id => Some(Element::Node),
+                            Some(Element::Edge) if key == ekey.id => Some(Element::Edge),
+                            _ => None,
+                        };
+                    }
+                    b"default" => default_of = key_of,
+                    _ => text_key = None,
+                }
+            }
+            Event::Text(ref e) => {
+                let text = e.unescape_and_decode(&reader)?;
+                match (&element, &text_key) {
+                    (Some(Element::Node), Some(Element::Node)) => {
+                        *vlabels.last_mut().unwrap() = Some(text)
+                    }
+                    (Some(Element::Edge), Some(Element::Edge)) => {
+                        *elabels.last_mut().unwrap() = Some(text)
+                    }
+                    (None, _) => {
+                        if default_of.0 {
+                            vkey.default = Some(text.clone());
+                        }
+                        if default_of.1 {
+                            ekey.default = Some(text);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Event::End(ref e) => match e.local_name() {
+                b"node" | b"edge" => {
+                    element = None;
+                    text_key = None;
+                }
+                b"default" => default_of = (false, false),
+                b"data" => text_key = None,
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    let vids: Vec<VId> = values_to_numbers(&nodes)?;
+    let vlabels: Vec<VLabel> = values_to_numbers(
+        &vlabels
+            .into_iter()
+            .map(|vlabel| vlabel.or_else(|| vkey.default.clone()))
+            .collect::<Vec<_>>(),
+    )?;
+    let elabels: Vec<ELabel> = values_to_numbers(
+        &elabels
+            .into_iter()
+            .map(|elabel| elabel.or_else(|| ekey.default.clone()))
+            .collect::<Vec<_>>(),
+    )?;
+    let vid = |id: &String| match indices.get(id) {
+        Some(&i) => Ok(vids[i]),
+        None => Err(GraphmlError::DanglingNode(id.clone())),
+    };
+    let mut edges = Vec::with_capacity(arcs.len());
+    for ((src, dst, directed), elabel) in arcs.iter().zip(elabels) {
+        let (src, dst) = (vid(src)?, vid(dst)?);
+        edges.push((src, dst, elabel));
+        if !directed && src != dst {
+            edges.push((dst, src, elabel));
+        }
+    }
+    Ok((vids.into_iter().zip(vlabels).collect(), edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_graphml() {
+        // As written by NetworkX.
+        let graphml = r#"<?xml version='1.0' encoding='utf-8'?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d1" for="edge" attr.name="type" attr.type="string"/>
+  <key id="d0" for="node" attr.name="kind" attr.type="string">
+    <default>person</default>
+  </key>
+  <graph edgedefault="undirected">
+    <node id="alice"><data key="d0">person</data></node>
+    <node id="bob"/>
+    <node id="acme"><data key="d0">company</data></node>
+    <edge source="alice" target="bob"><data key="d1">knows</data></edge>
+    <edge source="alice" target="acme"><data key="d1">works_at</data></edge>
+  </graph>
+</graphml>"#;
+        let attributes = LabelAttributes {
+            vlabel: "kind".to_string(),
+            elabel: "type".to_string(),
+        };
+        assert_eq!(
+            read_graphml(graphml.as_bytes(), &attributes).unwrap(),
+            (
+                vec![(0, 0), (1, 0), (2, 1)],
+                vec![(0, 1, 0), (1, 0, 0), (0, 2, 1), (2, 0, 1)]
+            )
+        );
+        let graphml =
+            r#"<graphml><graph><node id="1"/><edge source="1" target="2"/></graph></graphml>"#;
+        assert_eq!(
+            read_graphml(graphml.as_bytes(), &attributes)
+                .unwrap_err()
+                .to_string(),
+            "node `2` is not declared"
+        );
+    }
+
+    #[test]
+    fn test_read_graphml_shared_key() {
+        let graphml = r#"<graphml>
+  <key id="d0" for="all" attr.name="label"><desc>kind</desc><default>7</default></key>
+  <graph edgedefault="undirected">
+    <node id="1"/>
+    <node id="2"><data key="d0">3</data></node>
+    <edge source="1" target="2" directed="true"/>
+    <edge source="2" target="2"><data key="d0">5</data></edge>
+  </graph>
+</graphml>"#;
+        let attributes = LabelAttributes {
+            vlabel: "label".to_string(),
+            elabel: "label".to_string(),
+        };
+        assert_eq!(
+            read_graphml(graphml.as_bytes(), &attributes).unwrap(),
+            (vec![(1, 7), (2, 3)], vec![(1, 2, 7), (2, 2, 5)])
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    data_graph::{Edge, EdgeListOptions, EdgeListReader},
+    types::VId,
+};
+use std::{fs::File, io::BufReader};
+
+/// Number of vertices without label kept as examples in `LabelReport`.
+const NUM_EXAMPLES: usize = 10;
+
+#[derive(Debug, PartialEq)]
+pub struct LabelReport {
+    pub num_unlabelled_vertices: usize,
+    /// The first unlabelled vertices, in ascending order.
+    pub unlabelled_vertices: Vec<VId>,
+    pub num_unlabelled_edges: usize,
+}
+
+/// Sets the labels of vertices from a `vid vlabel` file, and those of edges from a
+/// `src dst elabel` file.
+///
+/// Both files follow the delimiter, comment, header and strict settings of `options`.  Vertices
+/// and edges missing from the files keep their labels, and the last label wins for duplicates.
+pub fn join_labels(
+    conn: &sqlite::Connection,
+    vlabels_file: Option<&File>,
+    elabels_file: Option<&File>,
+    options: &EdgeListOptions,
+) -> Result<LabelReport, Box<dyn std::error::Error>> {
+    let mut report = LabelReport {
+        num_unlabelled_vertices: 0,
+        unlabelled_vertices: vec![],
+        num_unlabelled_edges: 0,
+    };
+    if let Some(vlabels_file) = vlabels_file {
+        conn.execute("CREATE TEMP TABLE vlabels (vid INT PRIMARY KEY, vlabel INT)")?;
+        // `vid vlabel` lines are read as edges from `vid`, labelled by the second column.
+        let options = EdgeListOptions {
+            elabel_column: Some(1),
+            weight_column: None,
+            ..options.clone()
+        };
+        let result = fill_table(
+            conn,
+            "INSERT OR REPLACE INTO vlabels VALUES (?, ?)",
+            vlabels_file,
+            &options,
+            &|stat, edge| {
+                stat.bind(1, edge.src as i64)?;
+                stat.bind(2, edge.elabel as i64)
+            },
+        )
+        .and_then(|()| join_vlabels(conn, &mut report));
+        conn.execute("DROP TABLE vlabels")?;
+        result?;
+    }
+    if let Some(elabels_file) = elabels_file {
+        conn.execute(
+            "CREATE TEMP TABLE elabels (src INT, dst INT, elabel INT, PRIMARY KEY (src, dst))",
+        )?;
+        let options = EdgeListOptions {
+            elabel_column: Some(2),
+            weight_column: None,
+            ..options.clone()
+        };
+        let result = fill_table(
+            conn,
+            "INSERT OR REPLACE INTO elabels VALUES (?, ?, ?)",
+            elabels_file,
+            &options,
+            &|stat, edge| {
+                stat.bind(1, edge.src as i64)?;
+                stat.bind(2, edge.dst as i64)?;
+                stat.bind(3, edge.elabel as i64)
+            },
+        )
+        .and_then(|()| join_elabels(conn, &mut report));
+        conn.execute("DROP TABLE elabels")?;
+        result?;
+    }
+    Ok(report)
+}
+
+/// Runs `insert`, bound by `bind`, for every line of `file` in a transaction, which is rolled
+/// back on errors so that the connection is left outside of any transaction.
+fn fill_table(
+    conn: &sqlite::Connection,
+    insert: &str,
+    file: &File,
+    options: &EdgeListOptions,
+    bind: &dyn Fn(&mut sqlite::Statement, &Edge) -> sqlite::Result<()>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute("BEGIN")?;
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        let mut stat = conn.prepare(insert)?;
+        let mut reader = EdgeListReader::new(BufReader::new(file), options);
+        for edge in reader.by_ref() {
+            bind(&mut stat, &edge?)?;
+            stat.next()?;
+            stat.reset()?;
+        }
+        reader.finish()?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => conn.execute("END")?,
+        Err(_) => conn.execute("ROLLBACK")?,
+    }
+    result
+}
+
+fn join_vlabels(
+    conn: &sqlite::Connection,
+    report: &mut LabelReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute(
+        "UPDATE vertices SET vlabel = \
+         (SELECT vlabel FROM vlabels WHERE vlabels.vid = vertices.vid) \
+         WHERE vid IN (SELECT vid FROM vlabels)",
+    )?;
+    let mut stat = conn.prepare(
+        "SELECT vid FROM vertices WHERE vid NOT IN (SELECT vid FROM vlabels) ORDER BY vid",
+    )?;
+    while let sqlite::State::Row = stat.next()? {
+        if report.num_unlabelled_vertices < NUM_EXAMPLES {
+            report.unlabelled_vertices.push(stat.read::<i64>(0)? as VId);
+        }
+        report.num_unlabelled_vertices += 1;
+    }
+    Ok(())
+}
+
+fn join_elabels(
+    conn: &sqlite::Connection,
+    report: &mut LabelReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute(
+        "UPDATE edges SET elabel = \
+         (SELECT elabel FROM elabels WHERE elabels.src = edges.src AND elabels.dst = edges.dst) \
+         WHERE EXISTS \
+         (SELECT 1 FROM elabels WHERE elabels.src = edges.src AND elabels.dst = edges.dst)",
+    )?;
+    let mut stat = conn.prepare(
+        "SELECT COUNT(*) FROM edges WHERE NOT EXISTS \
+         (SELECT 1 FROM elabels WHERE elabels.src = edges.src AND elabels.dst = edges.dst)",
+    )?;
+    stat.next()?;
+    report.num_unlabelled_edges = stat.read::<i64>(0)? as usize;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{write_sqlite3, Graph};
+    use std::io::{Seek, SeekFrom, Write};
+
+    fn create_file(text: &str) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        write!(&mut file, "{}", text).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_join_labels() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            (1..=4).map(|vid| (vid, 0)),
+            vec![(1, 2, 0), (2, 3, 0), (3, 4, 0)],
+        )
+        .unwrap();
+        let vlabels = create_file("# vid label\n1 5\n2 6\n2 7\n9 1\n");
+        let elabels = create_file("2 3 8\n");
+        assert_eq!(
+            join_labels(
+                &conn,
+                Some(&vlabels),
+                Some(&elabels),
+                &EdgeListOptions::default()
+            )
+            .unwrap(),
+            LabelReport {
+                num_unlabelled_vertices: 2,
+                unlabelled_vertices: vec![3, 4],
+                num_unlabelled_edges: 2,
+            }
+        );
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        assert_eq!(
+            (0..4).map(|i| graph.vlabel(i)).collect::<Vec<_>>(),
+            vec![5, 7, 0, 0]
+        );
+        assert_eq!(graph.out_arcs(1), &[(2, 8)]);
+
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 0)], vec![]).unwrap();
+        let options = EdgeListOptions {
+            strict: true,
+            ..EdgeListOptions::default()
+        };
+        assert!(join_labels(&conn, Some(&create_file("1 x\n")), None, &options).is_err());
+        assert!(join_labels(&conn, None, Some(&create_file("1 1 x\n")), &options).is_err());
+        // Lines which are not UTF-8 fail while the labels are being inserted.
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"1 5\n\xff 6\n").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        assert!(join_labels(&conn, Some(&file), None, &options).is_err());
+        // Neither a transaction nor a temporary table is left behind.
+        assert!(conn.execute("BEGIN").is_ok());
+        assert!(conn.execute("CREATE TEMP TABLE vlabels (vid INT)").is_ok());
+        assert!(conn.execute("CREATE TEMP TABLE elabels (src INT)").is_ok());
+        assert!(conn.execute("END").is_ok());
+    }
+}
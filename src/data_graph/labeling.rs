@@ -0,0 +1,332 @@
+use crate::{
+    data_graph::RejectedLines,
+    types::{ELabel, VId, VLabel},
+};
+use derive_more::{Display, Error};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    io::BufRead,
+    str::FromStr,
+};
+
+/// Strategy to assign labels, see `sqlite3_to_sqlite3` and `gisp_to_gisp`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Labeling {
+    /// Labels drawn uniformly from `0..num_vlabels` and `0..num_elabels`.
+    Uniform {
+        num_vlabels: usize,
+        num_elabels: usize,
+    },
+    /// Label `k` drawn with probability proportional to `1 / (k + 1)^skew`.
+    Zipf {
+        num_vlabels: usize,
+        num_elabels: usize,
+        skew: f64,
+    },
+    /// Vertices by descending degree, and edges by descending sum of the degrees of their ends,
+    /// split into ranges of equal sizes labelled from 0.
+    Degree {
+        num_vlabels: usize,
+        num_elabels: usize,
+    },
+    /// Labels drawn with probabilities proportional to their weights.
+    Frequencies {
+        vlabels: Vec<(VLabel, f64)>,
+        elabels: Vec<(ELabel, f64)>,
+    },
+    /// Labels of the same vertices and edges of another data graph, the other ones being drawn
+    /// from its label frequencies.
+    Copy {
+        vlabels: HashMap<VId, VLabel>,
+        elabels: HashMap<(VId, VId), ELabel>,
+    },
+}
+
+impl Labeling {
+    /// Copies the labels of a data graph.
+    pub fn copy_from_sqlite3(conn: &sqlite::Connection) -> sqlite::Result<Self> {
+        let mut vlabels = HashMap::new();
+        let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+        while let sqlite::State::Row = stat.next()? {
+            vlabels.insert(stat.read::<i64>(0)? as VId, stat.read::<i64>(1)? as VLabel);
+        }
+        let mut elabels = HashMap::new();
+        let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+        while let sqlite::State::Row = stat.next()? {
+            elabels.insert(
+                (stat.read::<i64>(0)? as VId, stat.read::<i64>(1)? as VId),
+                stat.read::<i64>(2)? as ELabel,
+            );
+        }
+        Ok(Labeling::Copy { vlabels, elabels })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Labeling::Uniform { .. } => "uniform",
+            Labeling::Zipf { .. } => "zipf",
+            Labeling::Degree { .. } => "degree",
+            Labeling::Frequencies { .. } => "frequencies",
+            Labeling::Copy { .. } => "copy",
+        }
+    }
+
+    /// Returns how to draw vertex labels one at a time, or `None` for `Degree`, whose labels
+    /// depend on the degrees of all the vertices.
+    pub(crate) fn vlabel_draw(&self) -> Result<Option<Draw<'_, VId>>, NoLabels> {
+        let draw = match self {
+            Labeling::Uniform { num_vlabels, .. } => Draw::uniform(*num_vlabels),
+            Labeling::Zipf {
+                num_vlabels, skew, ..
+            } => Draw::zipf(*num_vlabels, *skew),
+            Labeling::Degree { num_vlabels, .. } => {
+                return match num_vlabels {
+                    0 => Err(NoLabels::new(self, "vertex")),
+                    _ => Ok(None),
+                }
+            }
+            Labeling::Frequencies { vlabels, .. } => Draw::weighted(vlabels.clone()),
+            Labeling::Copy { vlabels, .. } => Draw::copy(vlabels),
+        };
+        draw.map(Some).ok_or_else(|| NoLabels::new(self, "vertex"))
+    }
+
+    /// Returns how to draw edge labels one at a time, or `None` for `Degree`, whose labels
+    /// depend on the degrees of all the vertices.
+    pub(crate) fn elabel_draw(&self) -> Result<Option<Draw<'_, (VId, VId)>>, NoLabels> {
+        let draw = match self {
+            Labeling::Uniform { num_elabels, .. } => Draw::uniform(*num_elabels),
+            Labeling::Zipf {
+                num_elabels, skew, ..
+            } => Draw::zipf(*num_elabels, *skew),
+            Labeling::Degree { num_elabels, .. } => {
+                return match num_elabels {
+                    0 => Err(NoLabels::new(self, "edge")),
+                    _ => Ok(None),
+                }
+            }
+            Labeling::Frequencies { elabels, .. } => Draw::weighted(elabels.clone()),
+            Labeling::Copy { elabels, .. } => Draw::copy(elabels),
+        };
+        draw.map(Some).ok_or_else(|| NoLabels::new(self, "edge"))
+    }
+
+    /// Returns the labels of vertices of the given degrees.
+    ///
+    /// Vertices are copied by id if `vids` are given, otherwise `Copy` draws every label from
+    /// the frequencies.
+    pub fn vlabels<R: Rng + ?Sized>(
+        &self,
+        vids: Option<&[VId]>,
+        degrees: &[usize],
+        rng: &mut R,
+    ) -> Result<Vec<VLabel>, NoLabels> {
+        Ok(match (self.vlabel_draw()?, self) {
+            (Some(draw), _) => draw.labels(vids, degrees.len(), rng),
+            (None, Labeling::Degree { num_vlabels, .. }) => by_degree(*num_vlabels, degrees),
+            (None, _) => unreachable!(),
+        })
+    }
+
+    /// Returns the labels of edges whose ends have the given sums of degrees.
+    ///
+    /// Edges are copied by ends if `arcs` are given, otherwise `Copy` draws every label from the
+    /// frequencies.
+    pub fn elabels<R: Rng + ?Sized>(
+        &self,
+        arcs: Option<&[(VId, VId)]>,
+        degrees: &[usize],
+        rng: &mut R,
+    ) -> Result<Vec<ELabel>, NoLabels> {
+        Ok(match (self.elabel_draw()?, self) {
+            (Some(draw), _) => draw.labels(arcs, degrees.len(), rng),
+            (None, Labeling::Degree { num_elabels, .. }) => by_degree(*num_elabels, degrees),
+            (None, _) => unreachable!(),
+        })
+    }
+}
+
+/// Error of a labeling without any label to assign, e.g. with `--num-vlabels 0` or an empty
+/// frequency file.
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(fmt = "{} labeling has no {} label to assign", labeling, target)]
+pub struct NoLabels {
+    pub labeling: &'static str,
+    pub target: &'static str,
+}
+
+impl NoLabels {
+    fn new(labeling: &Labeling, target: &'static str) -> Self {
+        Self {
+            labeling: labeling.name(),
+            target,
+        }
+    }
+}
+
+/// Draws labels one at a time, for the labelings which do not depend on degrees.
+pub(crate) enum Draw<'a, K> {
+    Uniform(usize),
+    Weighted(Vec<(i16, f64)>, WeightedIndex<f64>),
+    Copy(&'a HashMap<K, i16>, Vec<(i16, f64)>, WeightedIndex<f64>),
+}
+
+impl<'a, K: Hash + Eq> Draw<'a, K> {
+    fn uniform(num_labels: usize) -> Option<Self> {
+        match num_labels {
+            0 => None,
+            _ => Some(Draw::Uniform(num_labels)),
+        }
+    }
+
+    fn zipf(num_labels: usize, skew: f64) -> Option<Self> {
+        Self::weighted(
+            (0..num_labels)
+                .map(|k| (k as i16, 1.0 / ((k + 1) as f64).powf(skew)))
+                .collect(),
+        )
+    }
+
+    /// Fails without labels, or if the weights are all zero.
+    fn weighted(weighted_labels: Vec<(i16, f64)>) -> Option<Self> {
+        let index = WeightedIndex::new(weighted_labels.iter().map(|&(_, weight)| weight)).ok()?;
+        Some(Draw::Weighted(weighted_labels, index))
+    }
+
+    fn copy(labels: &'a HashMap<K, i16>) -> Option<Self> {
+        let mut counts = BTreeMap::new();
+        for &label in labels.values() {
+            *counts.entry(label).or_insert(0.0) += 1.0;
+        }
+        let weighted_labels: Vec<(i16, f64)> = counts.into_iter().collect();
+        let index = WeightedIndex::new(weighted_labels.iter().map(|&(_, weight)| weight)).ok()?;
+        Some(Draw::Copy(labels, weighted_labels, index))
+    }
+
+    /// Returns the label of `key`, which is copied by `Copy` if it has one.
+    pub(crate) fn label<R: Rng + ?Sized>(&self, key: Option<&K>, rng: &mut R) -> i16 {
+        match self {
+            Draw::Uniform(num_labels) => rng.gen_range(0..*num_labels) as i16,
+            Draw::Weighted(weighted_labels, index) => weighted_labels[index.sample(rng)].0,
+            Draw::Copy(labels, weighted_labels, index) => key
+                .and_then(|key| labels.get(key).cloned())
+                .unwrap_or_else(|| weighted_labels[index.sample(rng)].0),
+        }
+    }
+
+    fn labels<R: Rng + ?Sized>(&self, keys: Option<&[K]>, len: usize, rng: &mut R) -> Vec<i16> {
+        match keys {
+            Some(keys) => keys.iter().map(|key| self.label(Some(key), rng)).collect(),
+            None => (0..len).map(|_| self.label(None, rng)).collect(),
+        }
+    }
+}
+
+/// Reads `label weight` lines, skipping blank lines and those starting with `#`.
+pub fn read_frequencies<T: FromStr, R: BufRead>(
+    reader: R,
+) -> Result<Vec<(T, f64)>, Box<dyn std::error::Error>> {
+    let (mut frequencies, mut rejected) = (vec![], vec![]);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.is_empty() || columns[0].starts_with('#') {
+            continue;
+        }
+        match (
+            columns[0].parse(),
+            columns.get(1).map(|weight| weight.parse::<f64>()),
+            columns.len(),
+        ) {
+            (Ok(label), Some(Ok(weight)), 2) if weight >= 0.0 => frequencies.push((label, weight)),
+            _ => rejected.push(i + 1),
+        }
+    }
+    if rejected.is_empty() {
+        Ok(frequencies)
+    } else {
+        Err(Box::new(RejectedLines {
+            line_numbers: rejected,
+        }))
+    }
+}
+
+fn by_degree(num_labels: usize, degrees: &[usize]) -> Vec<i16> {
+    let mut ranks: Vec<usize> = (0..degrees.len()).collect();
+    ranks.sort_by_key(|&i| std::cmp::Reverse(degrees[i]));
+    let mut labels = vec![0; degrees.len()];
+    for (rank, &i) in ranks.iter().enumerate() {
+        labels[i] = (rank * num_labels / degrees.len()) as i16;
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SEED;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_labeling() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
+        let degrees: Vec<usize> = (0..1000).map(|i| i % 10).collect();
+        let labeling = Labeling::Zipf {
+            num_vlabels: 4,
+            num_elabels: 1,
+            skew: 2.0,
+        };
+        let vlabels = labeling.vlabels(None, &degrees, &mut rng).unwrap();
+        let counts: Vec<usize> = (0..4)
+            .map(|k| vlabels.iter().filter(|&&vlabel| vlabel == k).count())
+            .collect();
+        assert!(counts.windows(2).all(|pair| pair[0] > pair[1]));
+
+        let labeling = Labeling::Degree {
+            num_vlabels: 2,
+            num_elabels: 3,
+        };
+        assert_eq!(
+            labeling.elabels(None, &[1, 5, 3, 4, 2, 6], &mut rng),
+            Ok(vec![2, 0, 1, 1, 2, 0])
+        );
+
+        let frequencies = read_frequencies("# label weight\n3 0\n7 1.5\n".as_bytes()).unwrap();
+        assert_eq!(frequencies, vec![(3, 0.0), (7, 1.5)]);
+        let labeling = Labeling::Frequencies {
+            vlabels: frequencies,
+            elabels: vec![],
+        };
+        assert_eq!(labeling.vlabels(None, &[0; 3], &mut rng), Ok(vec![7, 7, 7]));
+        assert_eq!(
+            labeling.elabels(None, &[0; 3], &mut rng),
+            Err(NoLabels {
+                labeling: "frequencies",
+                target: "edge"
+            })
+        );
+        assert!(read_frequencies::<VLabel, _>("3 x\n".as_bytes()).is_err());
+
+        let labeling = Labeling::Copy {
+            vlabels: vec![(1, 4), (2, 4)].into_iter().collect(),
+            elabels: HashMap::new(),
+        };
+        assert_eq!(
+            labeling.vlabels(Some(&[2, 9]), &[0; 2], &mut rng),
+            Ok(vec![4, 4])
+        );
+        assert!(labeling.elabels(None, &[0], &mut rng).is_err());
+
+        let labeling = Labeling::Uniform {
+            num_vlabels: 0,
+            num_elabels: 1,
+        };
+        assert!(labeling.vlabels(None, &[0], &mut rng).is_err());
+        assert_eq!(labeling.elabels(None, &[0], &mut rng), Ok(vec![0]));
+    }
+}
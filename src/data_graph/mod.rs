@@ -1,12 +1,57 @@
 //! Tools to create sqlite3 data graph.
+pub use attributes::{LabelAttributes, TooManyValues};
+pub use bin_to_sqlite3::bin_to_sqlite3;
+pub use compact_vids::{compact_vids, use_dense_vids, VIdOrder};
+pub use edge_list::{Edge, EdgeListOptions, EdgeListReader, RejectedLines};
+pub use gml_to_sqlite3::{gml_to_sqlite3, GmlError};
+pub use graph::Graph;
+pub use graph_to_sqlite3::graph_to_sqlite3;
+pub use graphml_to_sqlite3::{graphml_to_sqlite3, GraphmlError};
+pub use join_labels::{join_labels, LabelReport};
+pub use labeling::{read_frequencies, Labeling, NoLabels};
+pub use random_to_sqlite3::{random_to_sqlite3, InvalidParameters, Model};
+pub use snap_edges_to_bin::snap_edges_to_bin;
 pub use snap_edges_to_sqlite3::snap_edges_to_sqlite3;
+pub use sqlite3_to_bin::sqlite3_to_bin;
+pub use sqlite3_to_csr::{sqlite3_to_csr, CsrFiles};
+pub use sqlite3_to_datalog::sqlite3_to_datalog;
+pub use sqlite3_to_gisp::{sqlite3_to_gisp, Sampling, SamplingFailed, Shape};
+pub use sqlite3_to_gml::{sqlite3_to_gml, InvalidGmlKey};
+pub use sqlite3_to_graph::{sqlite3_to_graph, UnpairedArc};
 pub use sqlite3_to_graphflow::sqlite3_to_graphflow;
+pub use sqlite3_to_graphml::sqlite3_to_graphml;
+pub use sqlite3_to_mtx::sqlite3_to_mtx;
 pub use sqlite3_to_neo4j::sqlite3_to_neo4j;
+pub use sqlite3_to_ntriples::{sqlite3_to_ntriples, ELABEL_IRI, RDF_TYPE, VERTEX_IRI, VLABEL_IRI};
 pub use sqlite3_to_sqlite3::sqlite3_to_sqlite3;
 pub use write_sqlite3::write_sqlite3;
 
+mod attributes;
+pub mod bin;
+mod bin_to_sqlite3;
+mod compact_vids;
+mod dense;
+mod edge_list;
+mod gml_to_sqlite3;
+mod graph;
+pub mod graph_format;
+mod graph_to_sqlite3;
+mod graphml_to_sqlite3;
+mod join_labels;
+mod labeling;
+mod random_to_sqlite3;
+mod snap_edges_to_bin;
 mod snap_edges_to_sqlite3;
+mod sqlite3_to_bin;
+mod sqlite3_to_csr;
+mod sqlite3_to_datalog;
+mod sqlite3_to_gisp;
+mod sqlite3_to_gml;
+mod sqlite3_to_graph;
 mod sqlite3_to_graphflow;
+mod sqlite3_to_graphml;
+mod sqlite3_to_mtx;
 mod sqlite3_to_neo4j;
+mod sqlite3_to_ntriples;
 mod sqlite3_to_sqlite3;
 mod write_sqlite3;
@@ -0,0 +1,252 @@
+use crate::{
+    data_graph::{write_sqlite3, Labeling},
+    types::VId,
+    SEED,
+};
+use derive_more::{Display, Error};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// Number of times the pairing is restarted for random regular graphs.
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// `G(n, m)`: `m` distinct arcs drawn uniformly.
+    ErdosRenyi,
+    /// Preferential attachment, each new vertex having `m / n` or `m / n + 1` arcs to the former
+    /// ones, so that there are `m` arcs in total.
+    BarabasiAlbert,
+    /// Recursive matrix with the Graph500 probabilities `(0.57, 0.19, 0.19, 0.05)`.
+    Rmat,
+    /// Uniform `d`-regular undirected graph with `d = 2m / n`, one arc per edge.
+    Regular,
+}
+
+#[derive(Debug, Display, Error)]
+#[display(
+    fmt = "cannot generate {} edges on {} vertices",
+    num_edges,
+    num_vertices
+)]
+pub struct InvalidParameters {
+    pub num_vertices: usize,
+    pub num_edges: usize,
+}
+
+/// Generates a graph with vertex ids `0..num_vertices`, labelled by `labeling`.
+pub fn random_to_sqlite3(
+    conn: &sqlite::Connection,
+    model: Model,
+    num_vertices: usize,
+    num_edges: usize,
+    labeling: &Labeling,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
+    let arcs = match model {
+        Model::ErdosRenyi => erdos_renyi(num_vertices, num_edges, &mut rng),
+        Model::BarabasiAlbert => barabasi_albert(num_vertices, num_edges, &mut rng),
+        Model::Rmat => rmat(num_vertices, num_edges, &mut rng),
+        Model::Regular => regular(num_vertices, num_edges, &mut rng),
+    }
+    .ok_or(InvalidParameters {
+        num_vertices,
+        num_edges,
+    })?;
+    let mut degrees = vec![0; num_vertices];
+    for &(src, dst) in &arcs {
+        degrees[src] += 1;
+        degrees[dst] += 1;
+    }
+    let vids: Vec<VId> = (0..num_vertices).map(|vid| vid as VId).collect();
+    let vlabels = labeling.vlabels(Some(&vids), &degrees, &mut rng)?;
+    let arcs: Vec<(VId, VId)> = arcs
+        .into_iter()
+        .map(|(src, dst)| (src as VId, dst as VId))
+        .collect();
+    let elabels = labeling.elabels(
+        Some(&arcs),
+        &arcs
+            .iter()
+            .map(|&(src, dst)| degrees[src as usize] + degrees[dst as usize])
+            .collect::<Vec<_>>(),
+        &mut rng,
+    )?;
+    Ok(write_sqlite3(
+        conn,
+        vids.into_iter().zip(vlabels),
+        arcs.into_iter()
+            .zip(elabels)
+            .map(|((src, dst), elabel)| (src, dst, elabel)),
+    )?)
+}
+
+fn erdos_renyi<R: Rng + ?Sized>(n: usize, m: usize, rng: &mut R) -> Option<Vec<(usize, usize)>> {
+    if m > n * n.saturating_sub(1) {
+        return None;
+    }
+    let mut arcs = HashSet::with_capacity(m);
+    let mut results = Vec::with_capacity(m);
+    while results.len() < m {
+        let (src, dst) = (rng.gen_range(0..n), rng.gen_range(0..n));
+        if src != dst && arcs.insert((src, dst)) {
+            results.push((src, dst));
+        }
+    }
+    Some(results)
+}
+
+fn barabasi_albert<R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    rng: &mut R,
+) -> Option<Vec<(usize, usize)>> {
+    if m > n * n.saturating_sub(1) / 2 {
+        return None;
+    }
+    let mut results = Vec::with_capacity(m);
+    // Every vertex appears once per incident arc, plus once so isolated ones can be picked.
+    let mut endpoints = vec![0];
+    for src in 1..n {
+        // Spreads the remaining arcs over the remaining vertices, the first ones having fewer
+        // former vertices to attach to.
+        let (remaining, left) = (m - results.len(), n - src);
+        let k = src.min(remaining / left + usize::from(remaining % left > 0));
+        let mut targets = HashSet::with_capacity(k);
+        while targets.len() < k {
+            targets.insert(*endpoints.choose(rng).unwrap());
+        }
+        let mut targets: Vec<usize> = targets.into_iter().collect();
+        targets.sort_unstable();
+        for dst in targets {
+            results.push((src, dst));
+            endpoints.push(dst);
+            endpoints.push(src);
+        }
+        endpoints.push(src);
+    }
+    Some(results)
+}
+
+fn rmat<R: Rng + ?Sized>(n: usize, m: usize, rng: &mut R) -> Option<Vec<(usize, usize)>> {
+    if m > n * n.saturating_sub(1) {
+        return None;
+    }
+    let scale = n.next_power_of_two().trailing_zeros();
+    let mut arcs = HashSet::with_capacity(m);
+    let mut results = Vec::with_capacity(m);
+    while results.len() < m {
+        let (mut src, mut dst) = (0, 0);
+        for _ in 0..scale {
+            let p: f64 = rng.gen();
+            let (i, j) = if p < 0.57 {
+                (0, 0)
+            } else if p < 0.76 {
+                (0, 1)
+            } else if p < 0.95 {
+                (1, 0)
+            } else {
+                (1, 1)
+            };
+            src = src << 1 | i;
+            dst = dst << 1 | j;
+        }
+        if src < n && dst < n && src != dst && arcs.insert((src, dst)) {
+            results.push((src, dst));
+        }
+    }
+    Some(results)
+}
+
+fn regular<R: Rng + ?Sized>(n: usize, m: usize, rng: &mut R) -> Option<Vec<(usize, usize)>> {
+    if n == 0 {
+        return if m == 0 { Some(vec![]) } else { None };
+    }
+    let d = 2 * m / n;
+    if 2 * m != n * d || d >= n {
+        return None;
+    }
+    // Steger and Wormald: pairs of stubs are drawn until a suitable one is found, and the whole
+    // pairing restarts only when none turns up after `stubs.len()^2` draws.
+    for _ in 0..MAX_ATTEMPTS {
+        let mut stubs: Vec<usize> = (0..n).flat_map(|v| vec![v; d]).collect();
+        let mut edges = HashSet::with_capacity(m);
+        let mut results = Vec::with_capacity(m);
+        while !stubs.is_empty() {
+            let suitable = |i: usize, j: usize| {
+                let (u, v) = (stubs[i].min(stubs[j]), stubs[i].max(stubs[j]));
+                u != v && !edges.contains(&(u, v))
+            };
+            let pair = (0..stubs.len() * stubs.len())
+                .map(|_| (rng.gen_range(0..stubs.len()), rng.gen_range(0..stubs.len())))
+                .find(|&(i, j)| suitable(i, j));
+            let (i, j) = match pair {
+                Some(pair) => pair,
+                None => break,
+            };
+            let (u, v) = (stubs[i].min(stubs[j]), stubs[i].max(stubs[j]));
+            stubs.swap_remove(i.max(j));
+            stubs.swap_remove(i.min(j));
+            edges.insert((u, v));
+            results.push((u, v));
+        }
+        if stubs.is_empty() {
+            return Some(results);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::Graph;
+
+    #[test]
+    fn test_random_to_sqlite3() {
+        let labeling = Labeling::Uniform {
+            num_vlabels: 4,
+            num_elabels: 2,
+        };
+        for &model in &[
+            Model::ErdosRenyi,
+            Model::BarabasiAlbert,
+            Model::Rmat,
+            Model::Regular,
+        ] {
+            let conn = sqlite::open(":memory:").unwrap();
+            assert_eq!(
+                random_to_sqlite3(&conn, model, 100, 300, &labeling).unwrap(),
+                (100, 300)
+            );
+            let graph = Graph::from_sqlite3(&conn).unwrap();
+            let mut arcs = HashSet::new();
+            for v in 0..graph.num_vertices() {
+                assert!(graph.vlabel(v) < 4);
+                for &(u, elabel) in graph.out_arcs(v) {
+                    assert!(u != v && elabel < 2 && arcs.insert((v, u)));
+                }
+                if model == Model::Regular {
+                    assert_eq!(graph.out_arcs(v).len() + graph.in_arcs(v).len(), 6);
+                }
+            }
+            let other = sqlite::open(":memory:").unwrap();
+            random_to_sqlite3(&other, model, 100, 300, &labeling).unwrap();
+            let other = Graph::from_sqlite3(&other).unwrap();
+            assert!((0..100).all(|v| graph.out_arcs(v) == other.out_arcs(v)));
+        }
+        let conn = sqlite::open(":memory:").unwrap();
+        assert!(random_to_sqlite3(&conn, Model::Regular, 5, 6, &labeling).is_err());
+        let conn = sqlite::open(":memory:").unwrap();
+        assert!(random_to_sqlite3(&conn, Model::BarabasiAlbert, 5, 11, &labeling).is_err());
+        let conn = sqlite::open(":memory:").unwrap();
+        let labeling = Labeling::Degree {
+            num_vlabels: 2,
+            num_elabels: 1,
+        };
+        random_to_sqlite3(&conn, Model::BarabasiAlbert, 10, 20, &labeling).unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        assert_eq!(graph.vlabel(0), 0);
+        assert_eq!(graph.vlabel(9), 1);
+    }
+}
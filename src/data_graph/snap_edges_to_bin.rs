@@ -1,65 +1,78 @@
-use crate::types::{VId, VIdVIdELabel, VIdVLabel};
+use crate::{
+    data_graph::{
+        bin::{write_edge, write_vertex, BinHeader},
+        EdgeListOptions, EdgeListReader,
+    },
+    types::{VId, VIdVIdELabel, VIdVLabel},
+};
 use itertools::Itertools;
 use memmap::MmapMut;
-use regex::Regex;
 use std::{
     fs::File,
-    io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
+    io::{BufReader, BufWriter, Seek, SeekFrom, Write},
     mem::size_of,
     path::Path,
 };
 
-pub fn snap_edges_to_bin<P: AsRef<Path>>(path: P) -> std::io::Result<(File, File)> {
+/// Writes a SNAP edge list as a binary data graph, see `data_graph::bin` for the format.
+pub fn snap_edges_to_bin<P: AsRef<Path>, W: Write>(
+    path: P,
+    options: &EdgeListOptions,
+    buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
     let input_file = File::open(path)?;
-    let mut vertices = BufWriter::new(tempfile::tempfile()?);
     let mut edges = BufWriter::new(tempfile::tempfile()?);
 
-    let re = Regex::new(r"^(\d+)\D+(\d+)\D*$").unwrap();
     let mut vertices_temp = BufWriter::new(tempfile::tempfile()?);
-    for lines_item in BufReader::new(input_file).lines() {
-        let lines_item = lines_item?;
-        let line = lines_item.trim();
-        if let Some(caps) = re.captures(line) {
-            let src: VId = caps[1].parse().unwrap();
-            let dst: VId = caps[2].parse().unwrap();
-            vertices_temp.write_all(unsafe {
-                std::slice::from_raw_parts(
-                    &[src, dst] as *const _ as *const u8,
-                    2 * size_of::<VId>(),
-                )
-            })?;
-            edges.write_all(unsafe {
-                std::slice::from_raw_parts(
-                    &VIdVIdELabel(src, dst, 0) as *const _ as *const u8,
-                    size_of::<VIdVIdELabel>(),
-                )
-            })?;
-        }
+    let mut num_edges = 0;
+    let mut reader = EdgeListReader::new(BufReader::new(input_file), options);
+    for edge in reader.by_ref() {
+        let edge = edge?;
+        vertices_temp.write_all(unsafe {
+            std::slice::from_raw_parts(
+                &[edge.src, edge.dst] as *const _ as *const u8,
+                2 * size_of::<VId>(),
+            )
+        })?;
+        write_edge(&mut edges, VIdVIdELabel(edge.src, edge.dst, edge.elabel))?;
+        num_edges += 1;
     }
+    reader.finish()?;
     let vertices_temp = vertices_temp.into_inner()?;
-    let mut mmap = unsafe { MmapMut::map_mut(&vertices_temp)? };
-    let vids = unsafe {
-        std::slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut VId, mmap.len() / size_of::<VId>())
+    // Empty files cannot be mapped.
+    let mut mmap = match num_edges {
+        0 => None,
+        _ => Some(unsafe { MmapMut::map_mut(&vertices_temp)? }),
     };
-    vids.sort();
-    for &vid in vids.iter().dedup() {
-        vertices.write_all(unsafe {
-            std::slice::from_raw_parts(
-                &VIdVLabel(vid, 0) as *const _ as *const u8,
-                size_of::<VIdVLabel>(),
+    let vids: &mut [VId] = match &mut mmap {
+        Some(mmap) => unsafe {
+            std::slice::from_raw_parts_mut(
+                mmap.as_mut_ptr() as *mut VId,
+                mmap.len() / size_of::<VId>(),
             )
-        })?;
+        },
+        None => &mut [],
+    };
+    vids.sort_unstable();
+    let num_vertices = vids.iter().dedup().count();
+    BinHeader {
+        num_vertices,
+        num_edges,
     }
-    let (mut vertices, mut edges) = (vertices.into_inner()?, edges.into_inner()?);
-    vertices.seek(SeekFrom::Start(0))?;
+    .write(buf)?;
+    for &vid in vids.iter().dedup() {
+        write_vertex(buf, VIdVLabel(vid, 0))?;
+    }
+    let mut edges = edges.into_inner()?;
     edges.seek(SeekFrom::Start(0))?;
-    Ok((vertices, edges))
+    std::io::copy(&mut edges, buf)?;
+    Ok((num_vertices, num_edges))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Read;
+    use crate::data_graph::bin::read_bin;
     use tempfile::{NamedTempFile, TempPath};
 
     fn create_edges_file(edges: &[(VId, VId)]) -> TempPath {
@@ -74,30 +87,44 @@ mod tests {
     #[test]
     fn test_read_edges_file() {
         let path = create_edges_file(&[(1, 2), (1, 3), (1, 4), (5, 6), (5, 7), (5, 8)]);
-        let (vertices, edges) = snap_edges_to_bin(path).unwrap();
         let mut buffer = Vec::new();
-        BufReader::new(vertices).read_to_end(&mut buffer).unwrap();
-        let buffer_tmp: Vec<VIdVLabel> = (1..=8)
-            .into_iter()
-            .map(|vid| VIdVLabel(vid as VId, 0))
-            .collect();
-        assert_eq!(buffer, unsafe {
-            std::slice::from_raw_parts(
-                buffer_tmp.as_ptr() as *const _ as *const u8,
-                buffer_tmp.len() * size_of::<VIdVLabel>(),
-            )
-        });
+        assert_eq!(
+            snap_edges_to_bin(path, &EdgeListOptions::default(), &mut buffer).unwrap(),
+            (8, 6)
+        );
+        let (vertices, edges) = read_bin(&buffer).unwrap();
+        assert_eq!(
+            vertices
+                .iter()
+                .map(|&VIdVLabel(vid, vlabel)| (vid, vlabel))
+                .collect::<Vec<_>>(),
+            (1..=8).map(|vid| (vid, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            edges
+                .iter()
+                .map(|&VIdVIdELabel(src, dst, elabel)| (src, dst, elabel))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 2, 0),
+                (1, 3, 0),
+                (1, 4, 0),
+                (5, 6, 0),
+                (5, 7, 0),
+                (5, 8, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_empty_edges_file() {
+        let path = create_edges_file(&[]);
         let mut buffer = Vec::new();
-        BufReader::new(edges).read_to_end(&mut buffer).unwrap();
-        let buffer_tmp: Vec<VIdVIdELabel> = [(1, 2), (1, 3), (1, 4), (5, 6), (5, 7), (5, 8)]
-            .iter()
-            .map(|&(src, dst)| VIdVIdELabel(src as VId, dst as VId, 0))
-            .collect();
-        assert_eq!(buffer, unsafe {
-            std::slice::from_raw_parts(
-                buffer_tmp.as_ptr() as *const u8,
-                buffer_tmp.len() * size_of::<VIdVIdELabel>(),
-            )
-        });
+        assert_eq!(
+            snap_edges_to_bin(path, &EdgeListOptions::default(), &mut buffer).unwrap(),
+            (0, 0)
+        );
+        let (vertices, edges) = read_bin(&buffer).unwrap();
+        assert!(vertices.is_empty() && edges.is_empty());
     }
 }
@@ -1,34 +1,76 @@
-use crate::types::{VId, VLabel};
+use crate::{
+    data_graph::{EdgeListOptions, EdgeListReader},
+    types::VId,
+};
 use std::{
-    collections::BTreeSet,
+    cmp::Reverse,
+    collections::BinaryHeap,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    mem::size_of,
 };
 
+/// Number of rows inserted per transaction.
+const BATCH_SIZE: usize = 100_000;
+
+/// Number of edges between two progress reports.
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// Imports a SNAP edge list, keeping at most `memory_budget` bytes of vertex ids in memory.
+///
+/// Vertex ids are sorted in runs which are spilled to temporary files and merged at the end.
+/// Progress is reported to stderr if `progress` is set.
 pub fn snap_edges_to_sqlite3(
     conn: &sqlite::Connection,
     edges_file: &File,
+    options: &EdgeListOptions,
+    memory_budget: usize,
+    progress: bool,
 ) -> Result<(usize, usize), Box<dyn std::error::Error>> {
     create_tables(conn)?;
-    let mut vids = BTreeSet::new();
+    let run_capacity = (memory_budget / size_of::<VId>()).max(2);
+    let mut vids = Vec::with_capacity(run_capacity);
+    let mut runs = Vec::new();
     let mut num_edges = 0;
     conn.execute("BEGIN")?;
-    for lines_item in BufReader::new(edges_file).lines() {
-        let line = lines_item?;
-        if let &[Ok(src), Ok(dst)] = line
-            .split_whitespace()
-            .map(|n| n.parse::<VId>())
-            .collect::<Vec<_>>()
-            .as_slice()
-        {
-            vids.insert(src);
-            vids.insert(dst);
-            conn.execute(format!("INSERT INTO edges VALUES ({}, {}, 0)", src, dst))?;
-            num_edges += 1;
+    let mut stat = conn.prepare("INSERT INTO edges VALUES (?, ?, ?)")?;
+    let mut reader = EdgeListReader::new(BufReader::new(edges_file), options);
+    for edge in reader.by_ref() {
+        let edge = edge?;
+        stat.bind(1, edge.src as i64)?;
+        stat.bind(2, edge.dst as i64)?;
+        stat.bind(3, edge.elabel as i64)?;
+        stat.next()?;
+        stat.reset()?;
+        num_edges += 1;
+        if num_edges % BATCH_SIZE == 0 {
+            conn.execute("END; BEGIN")?;
+        }
+        if progress && num_edges % PROGRESS_INTERVAL == 0 {
+            eprintln!("{} edges, {} runs", num_edges, runs.len());
+        }
+        vids.push(edge.src);
+        vids.push(edge.dst);
+        if vids.len() + 2 > run_capacity {
+            runs.push(spill_run(&mut vids)?);
         }
     }
     conn.execute("END")?;
-    let num_vertices = insert_vertices(conn, vids.into_iter().map(|vid| (vid, 0)))?;
+    if progress && !reader.rejected().is_empty() {
+        eprintln!("{} lines rejected", reader.rejected().len());
+    }
+    reader.finish()?;
+    if !vids.is_empty() {
+        runs.push(spill_run(&mut vids)?);
+    }
+    drop(vids);
+    if progress {
+        eprintln!("{} edges, merging {} runs", num_edges, runs.len());
+    }
+    let num_vertices = insert_vertices(conn, runs)?;
+    if progress {
+        eprintln!("{} vertices, {} edges", num_vertices, num_edges);
+    }
     Ok((num_vertices, num_edges))
 }
 
@@ -37,16 +79,98 @@ fn create_tables(conn: &sqlite::Connection) -> sqlite::Result<()> {
     conn.execute("CREATE TABLE edges (src INT, dst INT, elabel INT)")
 }
 
-fn insert_vertices<VS>(conn: &sqlite::Connection, vertices: VS) -> sqlite::Result<usize>
-where
-    VS: IntoIterator<Item = (VId, VLabel)>,
-{
+/// Sorts and deduplicates `vids` into a temporary file, leaving `vids` empty.
+fn spill_run(vids: &mut Vec<VId>) -> std::io::Result<File> {
+    vids.sort_unstable();
+    vids.dedup();
+    let mut run = BufWriter::new(tempfile::tempfile()?);
+    for vid in vids.drain(..) {
+        run.write_all(&vid.to_ne_bytes())?;
+    }
+    let mut run = run.into_inner()?;
+    run.seek(SeekFrom::Start(0))?;
+    Ok(run)
+}
+
+fn read_vid<R: Read>(run: &mut R) -> std::io::Result<Option<VId>> {
+    let mut bytes = [0; size_of::<VId>()];
+    match run.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(VId::from_ne_bytes(bytes))),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Merges the sorted runs into the vertices table, without duplicates.
+fn insert_vertices(
+    conn: &sqlite::Connection,
+    runs: Vec<File>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut runs: Vec<BufReader<File>> = runs.into_iter().map(BufReader::new).collect();
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for (i, run) in runs.iter_mut().enumerate() {
+        if let Some(vid) = read_vid(run)? {
+            heap.push(Reverse((vid, i)));
+        }
+    }
     let mut num_vertices = 0;
+    let mut last = None;
     conn.execute("BEGIN")?;
-    for (vid, vlabel) in vertices {
-        conn.execute(format!("INSERT INTO vertices VALUES ({}, {})", vid, vlabel))?;
+    let mut stat = conn.prepare("INSERT INTO vertices VALUES (?, 0)")?;
+    while let Some(Reverse((vid, i))) = heap.pop() {
+        if let Some(next) = read_vid(&mut runs[i])? {
+            heap.push(Reverse((next, i)));
+        }
+        if last == Some(vid) {
+            continue;
+        }
+        last = Some(vid);
+        stat.bind(1, vid as i64)?;
+        stat.next()?;
+        stat.reset()?;
         num_vertices += 1;
+        if num_vertices % BATCH_SIZE == 0 {
+            conn.execute("END; BEGIN")?;
+        }
     }
     conn.execute("END")?;
     Ok(num_vertices)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::Graph;
+
+    #[test]
+    fn test_snap_edges_to_sqlite3() {
+        let mut edges_file = tempfile::tempfile().unwrap();
+        writeln!(&mut edges_file, "# FromNodeId\tToNodeId").unwrap();
+        let edges = [(5, 1), (1, 2), (9, 5), (2, 7), (7, 1), (3, 9), (2, 5)];
+        for (src, dst) in &edges {
+            writeln!(&mut edges_file, "{}\t{}", src, dst).unwrap();
+        }
+        edges_file.seek(SeekFrom::Start(0)).unwrap();
+        let conn = sqlite::open(":memory:").unwrap();
+        // Runs of two vertex ids.
+        assert_eq!(
+            snap_edges_to_sqlite3(
+                &conn,
+                &edges_file,
+                &EdgeListOptions::default(),
+                2 * size_of::<VId>(),
+                false
+            )
+            .unwrap(),
+            (6, 7)
+        );
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        assert_eq!(
+            (0..graph.num_vertices())
+                .map(|i| graph.vid(i))
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 7, 9]
+        );
+        assert_eq!(graph.out_arcs(graph.index(2).unwrap()), &[(3, 0), (4, 0)]);
+    }
+}
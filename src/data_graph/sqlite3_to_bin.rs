@@ -0,0 +1,82 @@
+use crate::{
+    data_graph::bin::{write_edge, write_vertex, BinHeader},
+    types::{ELabel, VId, VIdVIdELabel, VIdVLabel, VLabel},
+};
+use std::io::Write;
+
+/// Writes a binary data graph, see `data_graph::bin` for the format.
+pub fn sqlite3_to_bin<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let header = BinHeader {
+        num_vertices: select_count(conn, "vertices")?,
+        num_edges: select_count(conn, "edges")?,
+    };
+    header.write(buf)?;
+    let mut stat = conn.prepare("SELECT * FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        let vid: i64 = stat.read(0)?;
+        let vlabel: i64 = stat.read(1)?;
+        write_vertex(buf, VIdVLabel(vid as VId, vlabel as VLabel))?;
+    }
+    let mut stat = conn.prepare("SELECT * FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        let src: i64 = stat.read(0)?;
+        let dst: i64 = stat.read(1)?;
+        let elabel: i64 = stat.read(2)?;
+        write_edge(buf, VIdVIdELabel(src as VId, dst as VId, elabel as ELabel))?;
+    }
+    Ok((header.num_vertices, header.num_edges))
+}
+
+fn select_count(conn: &sqlite::Connection, table: &str) -> sqlite::Result<usize> {
+    let mut stat = conn.prepare(format!("SELECT COUNT(*) FROM {}", table))?;
+    stat.next()?;
+    Ok(stat.read::<i64>(0)? as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{bin_to_sqlite3, write_sqlite3};
+
+    #[test]
+    fn test_sqlite3_to_bin() {
+        let conn = sqlite::open(":memory:").unwrap();
+        let vertices = vec![(1, 1), (2, 2), (3, 3)];
+        let edges = vec![(1, 2, 12), (1, 3, 13)];
+        write_sqlite3(&conn, vertices.clone(), edges.clone()).unwrap();
+        let mut bin = tempfile::tempfile().unwrap();
+        assert_eq!(sqlite3_to_bin(&conn, &mut bin).unwrap(), (3, 2));
+        let new_conn = sqlite::open(":memory:").unwrap();
+        assert_eq!(bin_to_sqlite3(&new_conn, &bin).unwrap(), (3, 2));
+        let mut select_edges = new_conn.prepare("SELECT * FROM edges").unwrap();
+        let mut new_edges = vec![];
+        while let sqlite::State::Row = select_edges.next().unwrap() {
+            new_edges.push((
+                select_edges.read::<i64>(0).unwrap() as VId,
+                select_edges.read::<i64>(1).unwrap() as VId,
+                select_edges.read::<i64>(2).unwrap() as ELabel,
+            ));
+        }
+        assert_eq!(new_edges, edges);
+
+        let mut truncated = Vec::new();
+        sqlite3_to_bin(&conn, &mut truncated).unwrap();
+        truncated.pop();
+        assert!(crate::data_graph::bin::read_bin(&truncated).is_err());
+
+        let mut overflowing = Vec::new();
+        BinHeader {
+            num_vertices: usize::MAX / 2,
+            num_edges: 1,
+        }
+        .write(&mut overflowing)
+        .unwrap();
+        assert!(BinHeader::read(&overflowing).is_err());
+
+        let not_a_database = sqlite::open(":memory:").unwrap();
+        assert!(sqlite3_to_bin(&not_a_database, &mut Vec::new()).is_err());
+    }
+}
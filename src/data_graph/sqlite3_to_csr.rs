@@ -0,0 +1,183 @@
+use crate::{
+    data_graph::dense::{read_vlabels, select_num_slots, MmapArray},
+    types::{ELabel, VId, VLabel},
+};
+use std::{
+    fs::File,
+    io::{Error, ErrorKind},
+};
+
+/// Files of a compressed sparse row (CSR) adjacency, each a raw array of native-endian values.
+///
+/// The neighbours of vertex `v` are `neighbours[offsets[v]..offsets[v + 1]]`, sorted, with the
+/// labels of the edges at the same positions of `elabels`.
+pub struct CsrFiles<'a> {
+    /// `n + 1` values of `u64`.
+    pub offsets: &'a File,
+    /// `m` values of `VId`.
+    pub neighbours: &'a File,
+    /// `m` values of `ELabel`.
+    pub elabels: &'a File,
+}
+
+/// Writes the vertex labels as `n` values of `VLabel`, the CSR of out-edges and optionally the
+/// CSR of in-edges, where `n` is the largest `VId` plus one.
+///
+/// Vertices are indexed by `VId`, so missing ones have label 0 and no edges.  Files are
+/// memory-mapped, so they must be opened for both reading and writing.
+pub fn sqlite3_to_csr(
+    conn: &sqlite::Connection,
+    vlabels: &File,
+    out_csr: CsrFiles,
+    in_csr: Option<CsrFiles>,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let num_slots = select_num_slots(conn)?;
+    let mut vlabels = MmapArray::<VLabel>::new(vlabels, num_slots)?;
+    read_vlabels(conn, vlabels.as_mut_slice())?;
+    vlabels.flush()?;
+    let num_edges = write_csr(conn, num_slots, out_csr, false)?;
+    if let Some(in_csr) = in_csr {
+        write_csr(conn, num_slots, in_csr, true)?;
+    }
+    Ok((num_slots, num_edges))
+}
+
+/// Writes the CSR of in-edges if `reverse`, of out-edges otherwise, by counting sort.
+fn write_csr(
+    conn: &sqlite::Connection,
+    num_slots: usize,
+    csr: CsrFiles,
+    reverse: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut offsets_array = MmapArray::<u64>::new(csr.offsets, num_slots + 1)?;
+    let offsets = offsets_array.as_mut_slice();
+    let (key, other) = if reverse { (1, 0) } else { (0, 1) };
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        let (src, dst) = (stat.read::<i64>(0)?, stat.read::<i64>(1)?);
+        if src < 0 || src as usize >= num_slots || dst < 0 || dst as usize >= num_slots {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidData,
+                format!("edge ({}, {}) has an unknown vertex", src, dst),
+            )));
+        }
+        offsets[stat.read::<i64>(key)? as usize + 1] += 1;
+    }
+    for i in 0..num_slots {
+        offsets[i + 1] += offsets[i];
+    }
+    let num_edges = offsets[num_slots] as usize;
+    let mut cursors = MmapArray::<u64>::new(&tempfile::tempfile()?, num_slots)?;
+    let cursors = cursors.as_mut_slice();
+    cursors.copy_from_slice(&offsets[..num_slots]);
+    let mut neighbours_array = MmapArray::<VId>::new(csr.neighbours, num_edges)?;
+    let mut elabels_array = MmapArray::<ELabel>::new(csr.elabels, num_edges)?;
+    let (neighbours, elabels) = (
+        neighbours_array.as_mut_slice(),
+        elabels_array.as_mut_slice(),
+    );
+    stat.reset()?;
+    while let sqlite::State::Row = stat.next()? {
+        let cursor = &mut cursors[stat.read::<i64>(key)? as usize];
+        neighbours[*cursor as usize] = stat.read::<i64>(other)? as VId;
+        elabels[*cursor as usize] = stat.read::<i64>(2)? as ELabel;
+        *cursor += 1;
+    }
+    let mut adjacency = Vec::new();
+    for i in 0..num_slots {
+        let range = offsets[i] as usize..offsets[i + 1] as usize;
+        adjacency.clear();
+        adjacency.extend(
+            neighbours[range.clone()]
+                .iter()
+                .cloned()
+                .zip(elabels[range.clone()].iter().cloned()),
+        );
+        adjacency.sort_unstable();
+        for (j, &(neighbour, elabel)) in range.zip(&adjacency) {
+            neighbours[j] = neighbour;
+            elabels[j] = elabel;
+        }
+    }
+    offsets_array.flush()?;
+    neighbours_array.flush()?;
+    elabels_array.flush()?;
+    Ok(num_edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::write_sqlite3;
+    use std::{
+        convert::TryInto,
+        io::{Read, Seek, SeekFrom},
+        mem::size_of,
+    };
+
+    fn read_array<T, F: Fn(&[u8]) -> T>(mut file: &File, from_bytes: F, size: usize) -> Vec<T> {
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut bytes).unwrap();
+        bytes.chunks(size).map(from_bytes).collect()
+    }
+
+    #[test]
+    fn test_sqlite3_to_csr() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(0, 5), (1, 6), (3, 7)],
+            vec![(0, 3, 2), (0, 1, 4), (3, 1, 1), (0, 1, 0)],
+        )
+        .unwrap();
+        let files: Vec<File> = (0..7).map(|_| tempfile::tempfile().unwrap()).collect();
+        assert_eq!(
+            sqlite3_to_csr(
+                &conn,
+                &files[0],
+                CsrFiles {
+                    offsets: &files[1],
+                    neighbours: &files[2],
+                    elabels: &files[3],
+                },
+                Some(CsrFiles {
+                    offsets: &files[4],
+                    neighbours: &files[5],
+                    elabels: &files[6],
+                }),
+            )
+            .unwrap(),
+            (4, 4)
+        );
+        let vlabels = |file| {
+            read_array(
+                file,
+                |b| VLabel::from_ne_bytes(b.try_into().unwrap()),
+                size_of::<VLabel>(),
+            )
+        };
+        let offsets = |file| read_array(file, |b| u64::from_ne_bytes(b.try_into().unwrap()), 8);
+        let neighbours = |file| {
+            read_array(
+                file,
+                |b| VId::from_ne_bytes(b.try_into().unwrap()),
+                size_of::<VId>(),
+            )
+        };
+        let elabels = |file| {
+            read_array(
+                file,
+                |b| ELabel::from_ne_bytes(b.try_into().unwrap()),
+                size_of::<ELabel>(),
+            )
+        };
+        assert_eq!(vlabels(&files[0]), vec![5, 6, 0, 7]);
+        assert_eq!(offsets(&files[1]), vec![0, 3, 3, 3, 4]);
+        assert_eq!(neighbours(&files[2]), vec![1, 1, 3, 1]);
+        assert_eq!(elabels(&files[3]), vec![0, 4, 2, 1]);
+        assert_eq!(offsets(&files[4]), vec![0, 0, 3, 3, 4]);
+        assert_eq!(neighbours(&files[5]), vec![0, 0, 3, 0]);
+        assert_eq!(elabels(&files[6]), vec![0, 4, 1, 2]);
+    }
+}
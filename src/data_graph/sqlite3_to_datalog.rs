@@ -0,0 +1,53 @@
+use std::io::Write;
+
+/// Writes the `vertex(id, label)` and `edge(src, dst, label)` relations as tab-separated
+/// `.facts` files, as read by Soufflé.
+pub fn sqlite3_to_datalog<W: Write>(
+    conn: &sqlite::Connection,
+    vertices_buf: &mut W,
+    edges_buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut num_vertices = 0;
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            vertices_buf,
+            "{}\t{}",
+            stat.read::<i64>(0)?,
+            stat.read::<i64>(1)?
+        )?;
+        num_vertices += 1;
+    }
+    let mut num_edges = 0;
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            edges_buf,
+            "{}\t{}\t{}",
+            stat.read::<i64>(0)?,
+            stat.read::<i64>(1)?,
+            stat.read::<i64>(2)?
+        )?;
+        num_edges += 1;
+    }
+    Ok((num_vertices, num_edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::write_sqlite3;
+
+    #[test]
+    fn test_sqlite3_to_datalog() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 5), (2, 6)], vec![(2, 1, 3)]).unwrap();
+        let (mut vertices_buf, mut edges_buf) = (Vec::new(), Vec::new());
+        assert_eq!(
+            sqlite3_to_datalog(&conn, &mut vertices_buf, &mut edges_buf).unwrap(),
+            (2, 1)
+        );
+        assert_eq!(String::from_utf8(vertices_buf).unwrap(), "1\t5\n2\t6\n");
+        assert_eq!(String::from_utf8(edges_buf).unwrap(), "2\t1\t3\n");
+    }
+}
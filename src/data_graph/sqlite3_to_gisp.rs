@@ -0,0 +1,246 @@
+use crate::{
+    data_graph::Graph,
+    pattern_graph::Ast,
+    types::{ELabel, VId},
+    SEED,
+};
+use derive_more::{Display, Error};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Average degree from which a pattern is considered dense.
+const DENSE_AVG_DEGREE: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// The spanning tree the sampling discovered the vertices with.
+    Tree,
+    /// The induced subgraph, with at least one cycle.
+    Cyclic,
+    /// The induced subgraph, with an average degree of at least `DENSE_AVG_DEGREE`.
+    Dense,
+    /// The induced subgraph, with an average degree below `DENSE_AVG_DEGREE`.
+    Sparse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    Walk,
+    Bfs,
+}
+
+#[derive(Debug, Display, Error)]
+#[display(fmt = "failed to sample a pattern after {} attempts", MAX_ATTEMPTS)]
+pub struct SamplingFailed;
+
+/// Samples connected subgraphs of the data graph as patterns.
+///
+/// Every pattern keeps the labels of the data graph, so it has at least one match.
+pub fn sqlite3_to_gisp(
+    conn: &sqlite::Connection,
+    num_queries: usize,
+    num_vertices: usize,
+    shape: Shape,
+    sampling: Sampling,
+) -> Result<Vec<Ast>, Box<dyn std::error::Error>> {
+    let graph = Graph::from_sqlite3(conn)?;
+    let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
+    let mut queries = Vec::with_capacity(num_queries);
+    for _ in 0..num_queries {
+        queries.push(
+            (0..MAX_ATTEMPTS)
+                .find_map(|_| sample(&graph, num_vertices, shape, sampling, &mut rng))
+                .ok_or(SamplingFailed)?,
+        );
+    }
+    Ok(queries)
+}
+
+fn sample<R: Rng + ?Sized>(
+    graph: &Graph,
+    num_vertices: usize,
+    shape: Shape,
+    sampling: Sampling,
+    rng: &mut R,
+) -> Option<Ast> {
+    if graph.num_vertices() == 0 {
+        return None;
+    }
+    let (vertices, tree) = match sampling {
+        Sampling::Walk => walk(graph, num_vertices, rng)?,
+        Sampling::Bfs => bfs(graph, num_vertices, rng)?,
+    };
+    let vids: HashMap<usize, VId> = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (v, i as VId + 1))
+        .collect();
+    let arcs: BTreeSet<(usize, usize, ELabel)> = match shape {
+        Shape::Tree => tree,
+        _ => vertices
+            .iter()
+            .flat_map(|&src| {
+                let vids = &vids;
+                graph
+                    .out_arcs(src)
+                    .iter()
+                    .filter(move |&(dst, _)| *dst != src && vids.contains_key(dst))
+                    .map(move |&(dst, elabel)| (src, dst, elabel))
+            })
+            .collect(),
+    };
+    let avg_degree = 2.0 * arcs.len() as f64 / num_vertices as f64;
+    // Parallel and antiparallel arcs do not make a cycle, see `stats`.
+    let num_pairs = arcs
+        .iter()
+        .map(|&(src, dst, _)| (src.min(dst), src.max(dst)))
+        .collect::<BTreeSet<_>>()
+        .len();
+    let accepted = match shape {
+        Shape::Tree => true,
+        Shape::Cyclic => num_pairs >= num_vertices,
+        Shape::Dense => avg_degree >= DENSE_AVG_DEGREE,
+        Shape::Sparse => avg_degree < DENSE_AVG_DEGREE,
+    };
+    if !accepted {
+        return None;
+    }
+    let mut arcs: Vec<(VId, VId, ELabel)> = arcs
+        .into_iter()
+        .map(|(src, dst, elabel)| (vids[&src], vids[&dst], elabel))
+        .collect();
+    arcs.sort_unstable();
+    Some(Ast::new(
+        vertices
+            .iter()
+            .map(|&v| (vids[&v], graph.vlabel(v)))
+            .collect(),
+        arcs,
+        vec![],
+        None,
+    ))
+}
+
+type Sample = (Vec<usize>, BTreeSet<(usize, usize, ELabel)>);
+
+/// Returns the neighbors of `v` along with the arcs connecting them.
+fn neighbors(graph: &Graph, v: usize) -> Vec<(usize, (usize, usize, ELabel))> {
+    graph
+        .out_arcs(v)
+        .iter()
+        .map(|&(dst, elabel)| (dst, (v, dst, elabel)))
+        .chain(
+            graph
+                .in_arcs(v)
+                .iter()
+                .map(|&(src, elabel)| (src, (src, v, elabel))),
+        )
+        .filter(|&(u, _)| u != v)
+        .collect()
+}
+
+fn walk<R: Rng + ?Sized>(graph: &Graph, num_vertices: usize, rng: &mut R) -> Option<Sample> {
+    let mut current = rng.gen_range(0..graph.num_vertices());
+    let (mut vertices, mut tree) = (vec![current], BTreeSet::new());
+    for _ in 0..num_vertices * 100 {
+        if vertices.len() == num_vertices {
+            return Some((vertices, tree));
+        }
+        let (next, arc) = *neighbors(graph, current).choose(rng)?;
+        if !vertices.contains(&next) {
+            vertices.push(next);
+            tree.insert(arc);
+        }
+        current = next;
+    }
+    None
+}
+
+fn bfs<R: Rng + ?Sized>(graph: &Graph, num_vertices: usize, rng: &mut R) -> Option<Sample> {
+    let start = rng.gen_range(0..graph.num_vertices());
+    let (mut vertices, mut tree) = (vec![start], BTreeSet::new());
+    let mut queue = VecDeque::from(vec![start]);
+    while let Some(v) = queue.pop_front() {
+        let mut neighbors = neighbors(graph, v);
+        neighbors.shuffle(rng);
+        for (u, arc) in neighbors {
+            if vertices.len() == num_vertices {
+                return Some((vertices, tree));
+            }
+            if !vertices.contains(&u) {
+                vertices.push(u);
+                tree.insert(arc);
+                queue.push_back(u);
+            }
+        }
+    }
+    if vertices.len() == num_vertices {
+        Some((vertices, tree))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_graph::write_sqlite3,
+        pattern_graph::{stats, validate},
+        types::VLabel,
+    };
+
+    #[test]
+    fn test_sqlite3_to_gisp() {
+        let conn = sqlite::open(":memory:").unwrap();
+        let edges = vec![
+            (1, 2, 0),
+            (2, 3, 1),
+            (3, 1, 0),
+            (3, 4, 1),
+            (4, 5, 0),
+            (5, 6, 1),
+            (6, 4, 0),
+            (2, 5, 1),
+        ];
+        write_sqlite3(&conn, (1..=6).map(|vid| (vid, vid as VLabel % 2)), edges).unwrap();
+        for &sampling in &[Sampling::Walk, Sampling::Bfs] {
+            for &shape in &[Shape::Tree, Shape::Cyclic, Shape::Sparse] {
+                for ast in sqlite3_to_gisp(&conn, 5, 4, shape, sampling).unwrap() {
+                    let stats = stats(&ast);
+                    assert!(validate(&ast, None).is_empty());
+                    assert_eq!(stats.num_vertices, 4);
+                    match shape {
+                        Shape::Tree => assert!(stats.tree && stats.num_arcs == 3),
+                        Shape::Cyclic => assert!(stats.cyclic),
+                        _ => assert!(stats.num_arcs < 6),
+                    }
+                }
+            }
+        }
+        assert!(sqlite3_to_gisp(&conn, 1, 4, Shape::Dense, Sampling::Bfs).is_err());
+    }
+
+    #[test]
+    fn test_sqlite3_to_gisp_antiparallel() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            (1..=3).map(|vid| (vid, 0)),
+            vec![(1, 2, 0), (2, 1, 0), (2, 3, 0), (3, 2, 1)],
+        )
+        .unwrap();
+        for &sampling in &[Sampling::Walk, Sampling::Bfs] {
+            assert!(sqlite3_to_gisp(&conn, 1, 3, Shape::Cyclic, sampling).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sqlite3_to_gisp_dangling() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 0), (2, 0)], vec![(1, 2, 0), (2, 3, 0)]).unwrap();
+        assert!(sqlite3_to_gisp(&conn, 1, 2, Shape::Tree, Sampling::Bfs).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::data_graph::LabelAttributes;
+use derive_more::{Display, Error};
+use std::io::Write;
+
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(fmt = "`{}` cannot be the gml key of {} labels", key, element)]
+pub struct InvalidGmlKey {
+    pub key: String,
+    pub element: &'static str,
+}
+
+/// Writes a GML file of directed edges, with labels as integers keyed by `attributes`.
+///
+/// Keys must be a letter followed by letters, digits and underscores, and differ from the keys of
+/// ids and endpoints.
+pub fn sqlite3_to_gml<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+    attributes: &LabelAttributes,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    check_key(&attributes.vlabel, "node", &["id"])?;
+    check_key(&attributes.elabel, "edge", &["source", "target"])?;
+    writeln!(buf, "graph [")?;
+    writeln!(buf, "  directed 1")?;
+    let mut num_vertices = 0;
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            "  node [\n    id {}\n    {} {}\n  ]",
+            stat.read::<i64>(0)?,
+            attributes.vlabel,
+            stat.read::<i64>(1)?
+        )?;
+        num_vertices += 1;
+    }
+    let mut num_edges = 0;
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            "  edge [\n    source {}\n    target {}\n    {} {}\n  ]",
+            stat.read::<i64>(0)?,
+            stat.read::<i64>(1)?,
+            attributes.elabel,
+            stat.read::<i64>(2)?
+        )?;
+        num_edges += 1;
+    }
+    writeln!(buf, "]")?;
+    Ok((num_vertices, num_edges))
+}
+
+fn check_key(key: &str, element: &'static str, reserved: &[&str]) -> Result<(), InvalidGmlKey> {
+    let valid = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !reserved.contains(&key);
+    if valid {
+        Ok(())
+    } else {
+        Err(InvalidGmlKey {
+            key: key.to_string(),
+            element,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{gml_to_sqlite3, write_sqlite3, Graph};
+    use std::io::{Seek, SeekFrom};
+
+    #[test]
+    fn test_sqlite3_to_gml() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 3), (4, 2), (9, 3)],
+            vec![(1, 4, 0), (9, 1, 5), (4, 9, -1)],
+        )
+        .unwrap();
+        let attributes = LabelAttributes {
+            vlabel: "vlabel".to_string(),
+            elabel: "elabel".to_string(),
+        };
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            sqlite3_to_gml(&conn, &mut file, &attributes).unwrap(),
+            (3, 3)
+        );
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let new_conn = sqlite::open(":memory:").unwrap();
+        assert_eq!(
+            gml_to_sqlite3(&new_conn, &file, &attributes).unwrap(),
+            (3, 3)
+        );
+        let (graph, new_graph) = (
+            Graph::from_sqlite3(&conn).unwrap(),
+            Graph::from_sqlite3(&new_conn).unwrap(),
+        );
+        for i in 0..3 {
+            assert_eq!(new_graph.vid(i), graph.vid(i));
+            assert_eq!(new_graph.vlabel(i), graph.vlabel(i));
+            assert_eq!(new_graph.out_arcs(i), graph.out_arcs(i));
+        }
+    }
+
+    #[test]
+    fn test_sqlite3_to_gml_invalid_key() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 0)], vec![]).unwrap();
+        for &(vlabel, elabel) in &[
+            ("v label", "elabel"),
+            ("id", "elabel"),
+            ("vlabel", "target"),
+        ] {
+            let attributes = LabelAttributes {
+                vlabel: vlabel.to_string(),
+                elabel: elabel.to_string(),
+            };
+            assert!(sqlite3_to_gml(&conn, &mut Vec::new(), &attributes).is_err());
+        }
+        assert!(sqlite3_to_gml(&conn, &mut Vec::new(), &LabelAttributes::default()).is_ok());
+    }
+}
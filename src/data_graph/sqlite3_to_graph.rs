@@ -0,0 +1,99 @@
+use crate::{
+    data_graph::graph_format::write_graph,
+    types::{ELabel, VId, VLabel},
+};
+use derive_more::{Display, Error};
+use std::{collections::HashMap, io::Write};
+
+/// Error of an arc without an opposite one, which the undirected graph format cannot express.
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(
+    fmt = "graph has no directed arcs, found {} -> {} labelled {} without its reverse",
+    _0,
+    _1,
+    _2
+)]
+pub struct UnpairedArc(
+    #[error(not(source))] pub VId,
+    #[error(not(source))] pub VId,
+    #[error(not(source))] pub ELabel,
+);
+
+/// Writes a graph of subgraph matching benchmarks, see `data_graph::graph_format`.
+///
+/// Each pair of opposite arcs with the same label is written as one undirected edge, and
+/// self-loops as themselves.  Arcs left without an opposite one are rejected, the smallest one
+/// being reported.  Vertices are renumbered `0..n` in ascending order of `VId`.
+pub fn sqlite3_to_graph<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut vertices = Vec::new();
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices ORDER BY vid")?;
+    while let sqlite::State::Row = stat.next()? {
+        vertices.push((stat.read::<i64>(0)? as VId, stat.read::<i64>(1)? as VLabel));
+    }
+    let mut edges = Vec::new();
+    // Arcs written as edges and still waiting for their opposite arcs.
+    let mut unpaired: HashMap<(VId, VId, ELabel), usize> = HashMap::new();
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        let src = stat.read::<i64>(0)? as VId;
+        let dst = stat.read::<i64>(1)? as VId;
+        let elabel = stat.read::<i64>(2)? as ELabel;
+        if src != dst {
+            match unpaired.get_mut(&(dst, src, elabel)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    continue;
+                }
+                _ => *unpaired.entry((src, dst, elabel)).or_insert(0) += 1,
+            }
+        }
+        edges.push((src, dst, elabel));
+    }
+    if let Some(&(src, dst, elabel)) = unpaired
+        .iter()
+        .filter(|&(_, &count)| count > 0)
+        .map(|(arc, _)| arc)
+        .min()
+    {
+        return Err(Box::new(UnpairedArc(src, dst, elabel)));
+    }
+    write_graph(buf, &vertices, &edges)?;
+    Ok((vertices.len(), edges.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{graph_to_sqlite3, write_sqlite3};
+    use std::io::{Seek, SeekFrom};
+
+    #[test]
+    fn test_sqlite3_to_graph() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(3, 1), (1, 2), (2, 1)],
+            vec![(1, 2, 0), (3, 1, 5), (2, 1, 0), (1, 3, 5), (2, 2, 1)],
+        )
+        .unwrap();
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(sqlite3_to_graph(&conn, &mut file).unwrap(), (3, 3));
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let new_conn = sqlite::open(":memory:").unwrap();
+        assert_eq!(graph_to_sqlite3(&new_conn, &file).unwrap(), (3, 5));
+        let mut buf = Vec::new();
+        sqlite3_to_graph(&new_conn, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "t 3 3\nv 0 2 2\nv 1 1 3\nv 2 1 1\ne 0 1 0\ne 2 0 5\ne 1 1 1\n"
+        );
+
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 0), (2, 0)], vec![(1, 2, 0), (2, 1, 1)]).unwrap();
+        let error = sqlite3_to_graph(&conn, &mut Vec::new()).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&UnpairedArc(1, 2, 0)));
+    }
+}
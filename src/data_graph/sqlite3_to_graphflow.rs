@@ -1,61 +1,43 @@
-use crate::types::VLabel;
-use memmap::MmapMut;
-use std::{io::Write, mem::size_of};
+use crate::{
+    data_graph::dense::{read_vlabels, select_num_slots, MmapArray},
+    types::VLabel,
+};
+use std::io::Write;
 
 pub fn sqlite3_to_graphflow<W: Write>(
     conn: &sqlite::Connection,
     vertices_buf: &mut W,
     edges_buf: &mut W,
-) -> std::io::Result<(usize, usize)> {
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
     Ok((
         write_vertices(conn, vertices_buf)?,
         write_edges(conn, edges_buf)?,
     ))
 }
 
-fn write_vertices<W: Write>(conn: &sqlite::Connection, buf: &mut W) -> std::io::Result<usize> {
-    if let Some(max_vid) = select_max_vid(conn) {
-        let temp_vlabels = tempfile::tempfile()?;
-        temp_vlabels.set_len((max_vid + 1) as u64 * size_of::<VLabel>() as u64)?;
-        let mut mmap = unsafe { MmapMut::map_mut(&temp_vlabels)? };
-        let vlabels = unsafe {
-            std::slice::from_raw_parts_mut(
-                mmap.as_mut_ptr() as *mut VLabel,
-                mmap.len() / size_of::<VLabel>(),
-            )
-        };
-        let mut stat = conn.prepare("SELECT * FROM vertices").unwrap();
-        while let sqlite::State::Row = stat.next().unwrap() {
-            let vid: i64 = stat.read(0).unwrap();
-            let vlabel: i64 = stat.read(1).unwrap();
-            vlabels[vid as usize] = vlabel as VLabel;
-        }
-        for (vid, &vlabel) in vlabels.iter().enumerate() {
-            writeln!(buf, "{},{}", vid, vlabel)?;
-        }
-        Ok(max_vid as usize + 1)
-    } else {
-        Ok(0)
-    }
-}
-
-fn select_max_vid(conn: &sqlite::Connection) -> Option<i64> {
-    let mut stat = conn.prepare("SELECT MAX(vid) FROM vertices").unwrap();
-    if let sqlite::State::Row = stat.next().unwrap() {
-        let vid: i64 = stat.read(0).unwrap();
-        Some(vid)
-    } else {
-        None
+fn write_vertices<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let num_slots = select_num_slots(conn)?;
+    let mut vlabels = MmapArray::<VLabel>::new(&tempfile::tempfile()?, num_slots)?;
+    read_vlabels(conn, vlabels.as_mut_slice())?;
+    for (vid, &vlabel) in vlabels.as_mut_slice().iter().enumerate() {
+        writeln!(buf, "{},{}", vid, vlabel)?;
     }
+    Ok(num_slots)
 }
 
-fn write_edges<W: Write>(conn: &sqlite::Connection, buf: &mut W) -> std::io::Result<usize> {
+fn write_edges<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
     let mut count = 0;
-    let mut stat = conn.prepare("SELECT * FROM edges").unwrap();
-    while let sqlite::State::Row = stat.next().unwrap() {
-        let src: i64 = stat.read(0).unwrap();
-        let dst: i64 = stat.read(1).unwrap();
-        let elabel: i64 = stat.read(2).unwrap();
+    let mut stat = conn.prepare("SELECT * FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        let src: i64 = stat.read(0)?;
+        let dst: i64 = stat.read(1)?;
+        let elabel: i64 = stat.read(2)?;
         writeln!(buf, "{},{},{}", src, dst, elabel + 1)?;
         count += 1;
     }
@@ -85,5 +67,9 @@ mod tests {
         }
         assert_eq!(vertices_buf, vertices_temp);
         assert_eq!(edges_buf, edges_temp);
+
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(-1, 1), (2, 2)], vec![(-1, 2, 0)]).unwrap();
+        assert!(sqlite3_to_graphflow(&conn, &mut Vec::new(), &mut Vec::new()).is_err());
     }
 }
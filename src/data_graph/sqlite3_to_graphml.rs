@@ -0,0 +1,96 @@
+use crate::data_graph::LabelAttributes;
+use quick_xml::escape::escape;
+use std::io::Write;
+
+/// Writes a GraphML file of directed edges, with labels as `int` data named by `attributes`.
+pub fn sqlite3_to_graphml<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+    attributes: &LabelAttributes,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let escape = |name: &str| String::from_utf8(escape(name.as_bytes()).into_owned()).unwrap();
+    writeln!(buf, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        buf,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        buf,
+        r#"  <key id="vlabel" for="node" attr.name="{}" attr.type="int"/>"#,
+        escape(&attributes.vlabel)
+    )?;
+    writeln!(
+        buf,
+        r#"  <key id="elabel" for="edge" attr.name="{}" attr.type="int"/>"#,
+        escape(&attributes.elabel)
+    )?;
+    writeln!(buf, r#"  <graph edgedefault="directed">"#)?;
+    let mut num_vertices = 0;
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            r#"    <node id="{}"><data key="vlabel">{}</data></node>"#,
+            stat.read::<i64>(0)?,
+            stat.read::<i64>(1)?
+        )?;
+        num_vertices += 1;
+    }
+    let mut num_edges = 0;
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            r#"    <edge source="{}" target="{}"><data key="elabel">{}</data></edge>"#,
+            stat.read::<i64>(0)?,
+            stat.read::<i64>(1)?,
+            stat.read::<i64>(2)?
+        )?;
+        num_edges += 1;
+    }
+    writeln!(buf, "  </graph>")?;
+    writeln!(buf, "</graphml>")?;
+    Ok((num_vertices, num_edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::{graphml_to_sqlite3, write_sqlite3, Graph};
+    use std::io::{Seek, SeekFrom};
+
+    #[test]
+    fn test_sqlite3_to_graphml() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 3), (4, 2), (9, 3)],
+            vec![(1, 4, 0), (9, 1, 5), (4, 9, -1)],
+        )
+        .unwrap();
+        let attributes = LabelAttributes {
+            vlabel: "<kind>".to_string(),
+            elabel: "type".to_string(),
+        };
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            sqlite3_to_graphml(&conn, &mut file, &attributes).unwrap(),
+            (3, 3)
+        );
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let new_conn = sqlite::open(":memory:").unwrap();
+        assert_eq!(
+            graphml_to_sqlite3(&new_conn, &file, &attributes).unwrap(),
+            (3, 3)
+        );
+        let (graph, new_graph) = (
+            Graph::from_sqlite3(&conn).unwrap(),
+            Graph::from_sqlite3(&new_conn).unwrap(),
+        );
+        for i in 0..3 {
+            assert_eq!(new_graph.vid(i), graph.vid(i));
+            assert_eq!(new_graph.vlabel(i), graph.vlabel(i));
+            assert_eq!(new_graph.out_arcs(i), graph.out_arcs(i));
+        }
+    }
+}
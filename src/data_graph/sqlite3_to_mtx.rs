@@ -0,0 +1,103 @@
+use crate::{
+    data_graph::dense::{read_vlabels, select_num_slots, MmapArray},
+    types::VLabel,
+};
+use std::io::Write;
+
+/// Writes the adjacency as a Matrix Market coordinate matrix with edge labels plus one as values,
+/// and the vertex labels as a Matrix Market array.
+///
+/// Row and column `v + 1` stand for vertex `v`, so missing vertices have label 0 and no edges.
+/// Values are offset like the labels of `sqlite3_to_graphflow`, as readers may drop explicit
+/// zeros.  Readers sum duplicate entries, so parallel arcs are written as one entry with their
+/// smallest label.
+pub fn sqlite3_to_mtx<W: Write>(
+    conn: &sqlite::Connection,
+    matrix_buf: &mut W,
+    vlabels_buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let num_slots = select_num_slots(conn)?;
+    let mut vlabels = MmapArray::<VLabel>::new(&tempfile::tempfile()?, num_slots)?;
+    read_vlabels(conn, vlabels.as_mut_slice())?;
+    writeln!(vlabels_buf, "%%MatrixMarket matrix array integer general")?;
+    writeln!(vlabels_buf, "{} 1", num_slots)?;
+    for vlabel in vlabels.as_mut_slice() {
+        writeln!(vlabels_buf, "{}", vlabel)?;
+    }
+    let mut stat = conn.prepare("SELECT COUNT(*) FROM (SELECT DISTINCT src, dst FROM edges)")?;
+    stat.next()?;
+    let num_edges = stat.read::<i64>(0)? as usize;
+    writeln!(
+        matrix_buf,
+        "%%MatrixMarket matrix coordinate integer general"
+    )?;
+    writeln!(matrix_buf, "{} {} {}", num_slots, num_slots, num_edges)?;
+    let mut stat = conn.prepare("SELECT src, dst, MIN(elabel) FROM edges GROUP BY src, dst")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            matrix_buf,
+            "{} {} {}",
+            stat.read::<i64>(0)? + 1,
+            stat.read::<i64>(1)? + 1,
+            stat.read::<i64>(2)? + 1
+        )?;
+    }
+    Ok((num_slots, num_edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::write_sqlite3;
+
+    #[test]
+    fn test_sqlite3_to_mtx() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(0, 5), (2, 7)], vec![(2, 0, 3)]).unwrap();
+        let (mut matrix_buf, mut vlabels_buf) = (Vec::new(), Vec::new());
+        assert_eq!(
+            sqlite3_to_mtx(&conn, &mut matrix_buf, &mut vlabels_buf).unwrap(),
+            (3, 1)
+        );
+        assert_eq!(
+            String::from_utf8(matrix_buf).unwrap(),
+            "%%MatrixMarket matrix coordinate integer general\n3 3 1\n3 1 4\n"
+        );
+        assert_eq!(
+            String::from_utf8(vlabels_buf).unwrap(),
+            "%%MatrixMarket matrix array integer general\n3 1\n5\n0\n7\n"
+        );
+    }
+
+    #[test]
+    fn test_sqlite3_to_mtx_parallel() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(0, 0), (1, 0)],
+            vec![(0, 1, 4), (0, 1, 2), (1, 0, 3), (0, 1, 2)],
+        )
+        .unwrap();
+        let mut matrix_buf = Vec::new();
+        assert_eq!(
+            sqlite3_to_mtx(&conn, &mut matrix_buf, &mut Vec::new()).unwrap(),
+            (2, 2)
+        );
+        assert_eq!(
+            String::from_utf8(matrix_buf).unwrap(),
+            "%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 2 3\n2 1 4\n"
+        );
+    }
+
+    #[test]
+    fn test_sqlite3_to_mtx_zero_label() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(0, 0), (1, 0)], vec![(0, 1, 0)]).unwrap();
+        let mut matrix_buf = Vec::new();
+        sqlite3_to_mtx(&conn, &mut matrix_buf, &mut Vec::new()).unwrap();
+        assert_eq!(
+            String::from_utf8(matrix_buf).unwrap(),
+            "%%MatrixMarket matrix coordinate integer general\n2 2 1\n1 2 1\n"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::Write;
+
+/// Prefix of the IRIs of data vertices, followed by the `VId`.
+pub const VERTEX_IRI: &str = "urn:opgm:v:";
+/// Prefix of the IRIs of the classes of vertex labels, followed by the label.
+pub const VLABEL_IRI: &str = "urn:opgm:vlabel:";
+/// Prefix of the IRIs of the predicates of edge labels, followed by the label.
+pub const ELABEL_IRI: &str = "urn:opgm:elabel:";
+/// `rdf:type`.
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Writes the data graph as RDF N-Triples, with vertex labels as `rdf:type` classes and edge
+/// labels as predicates.
+///
+/// RDF graphs are sets of triples, so parallel edges of the same label are written once by
+/// triple stores.
+pub fn sqlite3_to_ntriples<W: Write>(
+    conn: &sqlite::Connection,
+    buf: &mut W,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut num_vertices = 0;
+    let mut stat = conn.prepare("SELECT vid, vlabel FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            "<{}{}> <{}> <{}{}> .",
+            VERTEX_IRI,
+            stat.read::<i64>(0)?,
+            RDF_TYPE,
+            VLABEL_IRI,
+            stat.read::<i64>(1)?
+        )?;
+        num_vertices += 1;
+    }
+    let mut num_edges = 0;
+    let mut stat = conn.prepare("SELECT src, dst, elabel FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        writeln!(
+            buf,
+            "<{}{}> <{}{}> <{}{}> .",
+            VERTEX_IRI,
+            stat.read::<i64>(0)?,
+            ELABEL_IRI,
+            stat.read::<i64>(2)?,
+            VERTEX_IRI,
+            stat.read::<i64>(1)?
+        )?;
+        num_edges += 1;
+    }
+    Ok((num_vertices, num_edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::write_sqlite3;
+
+    #[test]
+    fn test_sqlite3_to_ntriples() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(&conn, vec![(1, 5), (2, 6)], vec![(2, 1, 3)]).unwrap();
+        let mut buf = Vec::new();
+        assert_eq!(sqlite3_to_ntriples(&conn, &mut buf).unwrap(), (2, 1));
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "<urn:opgm:v:1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> \
+             <urn:opgm:vlabel:5> .\n\
+             <urn:opgm:v:2> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> \
+             <urn:opgm:vlabel:6> .\n\
+             <urn:opgm:v:2> <urn:opgm:elabel:3> <urn:opgm:v:1> .\n"
+        );
+    }
+}
@@ -1,72 +1,178 @@
-use crate::SEED;
+use crate::{
+    data_graph::{write_sqlite3, Labeling},
+    types::{ELabel, VId, VLabel},
+    SEED,
+};
 use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
 
+/// Copies a data graph, assigning new labels by `labeling`.
+///
+/// Vertices and edges are streamed from one database to the other, except for
+/// `Labeling::Degree` which reads the whole graph first.
 pub fn sqlite3_to_sqlite3(
     old_conn: &sqlite::Connection,
     new_conn: &sqlite::Connection,
-    num_vlabels: usize,
-    num_elabels: usize,
-) -> (usize, usize) {
+    labeling: &Labeling,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
     let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
-    create_tables(new_conn).unwrap();
-    (
-        write_vertices(old_conn, new_conn, num_vlabels, &mut rng),
-        write_edges(old_conn, new_conn, num_elabels, &mut rng),
-    )
+    match (labeling.vlabel_draw()?, labeling.elabel_draw()?) {
+        (Some(vlabel_draw), Some(elabel_draw)) => {
+            create_tables(new_conn)?;
+            Ok((
+                write_vertices(old_conn, new_conn, |vid| {
+                    vlabel_draw.label(Some(&vid), &mut rng)
+                })?,
+                write_edges(old_conn, new_conn, |src, dst| {
+                    elabel_draw.label(Some(&(src, dst)), &mut rng)
+                })?,
+            ))
+        }
+        _ => relabel_by_degree(old_conn, new_conn, labeling, &mut rng),
+    }
 }
 
 fn create_tables(conn: &sqlite::Connection) -> sqlite::Result<()> {
-    conn.execute("CREATE TABLE vertices (vid INT, vlabel INT)")
-        .unwrap();
+    conn.execute("CREATE TABLE vertices (vid INT, vlabel INT)")?;
     conn.execute("CREATE TABLE edges (src INT, dst INT, elabel INT)")
 }
 
-fn write_vertices<R: Rng + ?Sized>(
+fn write_vertices<F: FnMut(VId) -> VLabel>(
     old_conn: &sqlite::Connection,
     new_conn: &sqlite::Connection,
-    num_vlabels: usize,
-    rng: &mut R,
-) -> usize {
+    mut vlabel: F,
+) -> sqlite::Result<usize> {
     let mut count = 0;
-    let mut old_stat = old_conn.prepare("SELECT * FROM vertices").unwrap();
-    new_conn.execute("BEGIN").unwrap();
-    let mut stat = new_conn
-        .prepare("INSERT INTO vertices VALUES (?, ?)")
-        .unwrap();
-    while let sqlite::State::Row = old_stat.next().unwrap() {
-        let vid: i64 = old_stat.read(0).unwrap();
-        stat.bind(1, vid).unwrap();
-        stat.bind(2, rng.gen_range(0..num_vlabels) as i64).unwrap();
-        stat.next().unwrap();
-        stat.reset().unwrap();
+    let mut old_stat = old_conn.prepare("SELECT vid FROM vertices")?;
+    new_conn.execute("BEGIN")?;
+    let mut stat = new_conn.prepare("INSERT INTO vertices VALUES (?, ?)")?;
+    while let sqlite::State::Row = old_stat.next()? {
+        let vid: i64 = old_stat.read(0)?;
+        stat.bind(1, vid)?;
+        stat.bind(2, vlabel(vid as VId) as i64)?;
+        stat.next()?;
+        stat.reset()?;
         count += 1;
     }
-    new_conn.execute("END").unwrap();
-    count
+    new_conn.execute("END")?;
+    Ok(count)
 }
 
-fn write_edges<R: Rng + ?Sized>(
+fn write_edges<F: FnMut(VId, VId) -> ELabel>(
     old_conn: &sqlite::Connection,
     new_conn: &sqlite::Connection,
-    num_elabels: usize,
-    rng: &mut R,
-) -> usize {
+    mut elabel: F,
+) -> sqlite::Result<usize> {
     let mut count = 0;
-    let mut old_stat = old_conn.prepare("SELECT * FROM edges").unwrap();
-    new_conn.execute("BEGIN").unwrap();
-    let mut stat = new_conn
-        .prepare("INSERT INTO edges VALUES (?, ?, ?)")
-        .unwrap();
-    while let sqlite::State::Row = old_stat.next().unwrap() {
-        let src: i64 = old_stat.read(0).unwrap();
-        let dst: i64 = old_stat.read(1).unwrap();
-        stat.bind(1, src).unwrap();
-        stat.bind(2, dst).unwrap();
-        stat.bind(3, rng.gen_range(0..num_elabels) as i64).unwrap();
-        stat.next().unwrap();
-        stat.reset().unwrap();
+    let mut old_stat = old_conn.prepare("SELECT src, dst FROM edges")?;
+    new_conn.execute("BEGIN")?;
+    let mut stat = new_conn.prepare("INSERT INTO edges VALUES (?, ?, ?)")?;
+    while let sqlite::State::Row = old_stat.next()? {
+        let src: i64 = old_stat.read(0)?;
+        let dst: i64 = old_stat.read(1)?;
+        stat.bind(1, src)?;
+        stat.bind(2, dst)?;
+        stat.bind(3, elabel(src as VId, dst as VId) as i64)?;
+        stat.next()?;
+        stat.reset()?;
         count += 1;
     }
-    new_conn.execute("END").unwrap();
-    count
+    new_conn.execute("END")?;
+    Ok(count)
+}
+
+/// Reads the whole graph, for labelings depending on the degrees.
+fn relabel_by_degree<R: Rng + ?Sized>(
+    old_conn: &sqlite::Connection,
+    new_conn: &sqlite::Connection,
+    labeling: &Labeling,
+    rng: &mut R,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut vids = Vec::new();
+    let mut stat = old_conn.prepare("SELECT vid FROM vertices")?;
+    while let sqlite::State::Row = stat.next()? {
+        vids.push(stat.read::<i64>(0)? as VId);
+    }
+    let mut arcs = Vec::new();
+    let mut stat = old_conn.prepare("SELECT src, dst FROM edges")?;
+    while let sqlite::State::Row = stat.next()? {
+        arcs.push((stat.read::<i64>(0)? as VId, stat.read::<i64>(1)? as VId));
+    }
+    let mut degrees: HashMap<VId, usize> = HashMap::with_capacity(vids.len());
+    for &(src, dst) in &arcs {
+        *degrees.entry(src).or_insert(0) += 1;
+        *degrees.entry(dst).or_insert(0) += 1;
+    }
+    let degree = |vid| degrees.get(&vid).cloned().unwrap_or(0);
+    let vlabels = labeling.vlabels(
+        Some(&vids),
+        &vids.iter().map(|&vid| degree(vid)).collect::<Vec<_>>(),
+        rng,
+    )?;
+    let elabels = labeling.elabels(
+        Some(&arcs),
+        &arcs
+            .iter()
+            .map(|&(src, dst)| degree(src) + degree(dst))
+            .collect::<Vec<_>>(),
+        rng,
+    )?;
+    Ok(write_sqlite3(
+        new_conn,
+        vids.into_iter().zip(vlabels),
+        arcs.into_iter()
+            .zip(elabels)
+            .map(|((src, dst), elabel)| (src, dst, elabel)),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_graph::Graph;
+
+    #[test]
+    fn test_sqlite3_to_sqlite3() {
+        let old_conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &old_conn,
+            vec![(1, 0), (2, 0), (3, 0)],
+            vec![(1, 2, 0), (1, 3, 0)],
+        )
+        .unwrap();
+        let new_conn = sqlite::open(":memory:").unwrap();
+        let labeling = Labeling::Degree {
+            num_vlabels: 3,
+            num_elabels: 1,
+        };
+        assert_eq!(
+            sqlite3_to_sqlite3(&old_conn, &new_conn, &labeling).unwrap(),
+            (3, 2)
+        );
+        let graph = Graph::from_sqlite3(&new_conn).unwrap();
+        assert_eq!(
+            (0..3).map(|i| graph.vlabel(i)).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        let new_conn = sqlite::open(":memory:").unwrap();
+        let labeling = Labeling::Copy {
+            vlabels: vec![(1, 7), (2, 8)].into_iter().collect(),
+            elabels: vec![((1, 3), 9)].into_iter().collect(),
+        };
+        assert_eq!(
+            sqlite3_to_sqlite3(&old_conn, &new_conn, &labeling).unwrap(),
+            (3, 2)
+        );
+        let graph = Graph::from_sqlite3(&new_conn).unwrap();
+        assert_eq!((graph.vlabel(0), graph.vlabel(1)), (7, 8));
+        assert_eq!(graph.out_arcs(0)[1], (2, 9));
+
+        let new_conn = sqlite::open(":memory:").unwrap();
+        let labeling = Labeling::Frequencies {
+            vlabels: vec![(1, 1.0)],
+            elabels: vec![(2, 0.0)],
+        };
+        assert!(sqlite3_to_sqlite3(&old_conn, &new_conn, &labeling).is_err());
+    }
 }
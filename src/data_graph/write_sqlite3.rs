@@ -55,6 +55,7 @@ where
 mod tests {
     use super::*;
 
+    #[allow(clippy::type_complexity)]
     fn read_vertices_edges(
         conn: &sqlite::Connection,
     ) -> (Vec<(VId, VLabel)>, Vec<(VId, VId, ELabel)>) {
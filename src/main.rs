@@ -5,21 +5,45 @@ use clap::{
 use derive_more::{Display, Error};
 use opgm_tools::{
     data_graph::{
-        snap_edges_to_sqlite3, sqlite3_to_graphflow, sqlite3_to_neo4j, sqlite3_to_sqlite3,
+        bin_to_sqlite3, compact_vids, gml_to_sqlite3, graph_to_sqlite3, graphml_to_sqlite3,
+        join_labels, random_to_sqlite3, read_frequencies, snap_edges_to_bin, snap_edges_to_sqlite3,
+        sqlite3_to_bin, sqlite3_to_csr, sqlite3_to_datalog, sqlite3_to_gisp, sqlite3_to_gml,
+        sqlite3_to_graph, sqlite3_to_graphflow, sqlite3_to_graphml, sqlite3_to_mtx,
+        sqlite3_to_neo4j, sqlite3_to_ntriples, sqlite3_to_sqlite3, use_dense_vids, CsrFiles,
+        EdgeListOptions, Graph, LabelAttributes, Labeling, Model, Sampling, Shape, VIdOrder,
+    },
+    pattern_graph::{
+        break_symmetry, find_matches, find_sql_matches, gisp_to_cypher, gisp_to_datalog,
+        gisp_to_gisp, gisp_to_gisp_embedded, gisp_to_graph, gisp_to_graphflow, gisp_to_sparql,
+        gisp_to_sql, gisp_to_star, graph_to_gisp, lint, parse, stats, Semantics, Target,
     },
-    pattern_graph::{gisp_to_cypher, gisp_to_gisp, gisp_to_graphflow, gisp_to_star, parse},
     types::VId,
 };
 use std::{
     error::Error,
-    fs::File,
+    ffi::OsStr,
+    fs::{File, OpenOptions},
     io::{BufReader, BufWriter, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 #[derive(Debug, Display, Error)]
 struct InvalidPath;
 
+#[derive(Debug, Display, Error)]
+#[display(fmt = "--{} is required by --labeling {}", option, labeling)]
+struct MissingOption {
+    option: &'static str,
+    labeling: String,
+}
+
+#[derive(Debug, Display, Error)]
+#[display(fmt = "{} of {} gisp files have problems", num_failed, num_files)]
+struct LintFailed {
+    num_failed: usize,
+    num_files: usize,
+}
+
 fn handle_createdb(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     match matches.value_of("FMT").unwrap() {
         "snap_edges" => {
@@ -27,6 +51,259 @@ fn handle_createdb(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
             snap_edges_to_sqlite3(
                 &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
                 &File::open(matches.value_of("INPUT").unwrap())?,
+                &edge_list_options(matches)?,
+                matches
+                    .value_of("memory-budget")
+                    .unwrap()
+                    .parse::<usize>()?
+                    << 20,
+                matches.is_present("progress"),
+            )?;
+        }
+        "graph" => {
+            File::create(matches.value_of("SQLITE3").unwrap())?;
+            graph_to_sqlite3(
+                &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+                &File::open(matches.value_of("INPUT").unwrap())?,
+            )?;
+        }
+        "graphml" => {
+            File::create(matches.value_of("SQLITE3").unwrap())?;
+            graphml_to_sqlite3(
+                &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+                &File::open(matches.value_of("INPUT").unwrap())?,
+                &label_attributes(matches),
+            )?;
+        }
+        "gml" => {
+            File::create(matches.value_of("SQLITE3").unwrap())?;
+            gml_to_sqlite3(
+                &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+                &File::open(matches.value_of("INPUT").unwrap())?,
+                &label_attributes(matches),
+            )?;
+        }
+        "bin" => {
+            File::create(matches.value_of("SQLITE3").unwrap())?;
+            bin_to_sqlite3(
+                &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+                &File::open(matches.value_of("INPUT").unwrap())?,
+            )?;
+        }
+        fmt => {
+            let model = match fmt {
+                "erdos_renyi" => Model::ErdosRenyi,
+                "barabasi_albert" => Model::BarabasiAlbert,
+                "rmat" => Model::Rmat,
+                "regular" => Model::Regular,
+                _ => unreachable!(),
+            };
+            let num_vertices: usize = matches.value_of("num-vertices").unwrap().parse()?;
+            let num_edges = match (matches.value_of("num-edges"), matches.value_of("density")) {
+                (Some(num_edges), _) => num_edges.parse()?,
+                (None, Some(density)) => (density.parse::<f64>()?
+                    * (num_vertices * num_vertices.saturating_sub(1)) as f64)
+                    .round() as usize,
+                _ => unreachable!(),
+            };
+            File::create(matches.value_of("SQLITE3").unwrap())?;
+            random_to_sqlite3(
+                &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+                model,
+                num_vertices,
+                num_edges,
+                &labeling(matches)?,
+            )?;
+        }
+    }
+    if matches.is_present("vlabels") || matches.is_present("elabels") {
+        let report = join_labels(
+            &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+            matches
+                .value_of("vlabels")
+                .map(File::open)
+                .transpose()?
+                .as_ref(),
+            matches
+                .value_of("elabels")
+                .map(File::open)
+                .transpose()?
+                .as_ref(),
+            &edge_list_options(matches)?,
+        )?;
+        if matches.is_present("vlabels") && report.num_unlabelled_vertices > 0 {
+            eprintln!(
+                "{} vertices without label, starting with {:?}",
+                report.num_unlabelled_vertices, report.unlabelled_vertices
+            );
+        }
+        if matches.is_present("elabels") && report.num_unlabelled_edges > 0 {
+            eprintln!("{} edges without label", report.num_unlabelled_edges);
+        }
+    }
+    if let Some(order) = matches.value_of("compact") {
+        compact_vids(
+            &sqlite::open(matches.value_of("SQLITE3").unwrap())?,
+            match order {
+                "degree" => VIdOrder::Degree,
+                "bfs" => VIdOrder::Bfs,
+                _ => unreachable!(),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn edge_list_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("delimiter")
+            .long("delimiter")
+            .takes_value(true)
+            .help("Splits columns on whitespace if not given"),
+        Arg::with_name("comment").long("comment").default_value("#"),
+        Arg::with_name("skip-header")
+            .long("skip-header")
+            .default_value("0"),
+        Arg::with_name("elabel-column")
+            .long("elabel-column")
+            .takes_value(true),
+        Arg::with_name("weight-column")
+            .long("weight-column")
+            .takes_value(true),
+        Arg::with_name("strict")
+            .long("strict")
+            .help("Fails with the rejected line numbers instead of skipping them"),
+    ]
+}
+
+fn edge_list_options(matches: &ArgMatches) -> Result<EdgeListOptions, Box<dyn Error>> {
+    Ok(EdgeListOptions {
+        delimiter: match matches.value_of("delimiter") {
+            Some("\\t") => Some('\t'),
+            Some(delimiter) => Some(delimiter.parse()?),
+            None => None,
+        },
+        comment: matches.value_of("comment").unwrap().to_string(),
+        skip_header: matches.value_of("skip-header").unwrap().parse()?,
+        elabel_column: matches
+            .value_of("elabel-column")
+            .map(|column| column.parse())
+            .transpose()?,
+        weight_column: matches
+            .value_of("weight-column")
+            .map(|column| column.parse())
+            .transpose()?,
+        strict: matches.is_present("strict"),
+    })
+}
+
+fn label_attribute_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("vlabel-attr")
+            .long("vlabel-attr")
+            .help("Attribute of graphml and gml nodes holding vertex labels")
+            .default_value("vlabel"),
+        Arg::with_name("elabel-attr")
+            .long("elabel-attr")
+            .help("Attribute of graphml and gml edges holding edge labels")
+            .default_value("elabel"),
+    ]
+}
+
+fn label_attributes(matches: &ArgMatches) -> LabelAttributes {
+    LabelAttributes {
+        vlabel: matches.value_of("vlabel-attr").unwrap().to_string(),
+        elabel: matches.value_of("elabel-attr").unwrap().to_string(),
+    }
+}
+
+/// Options of `labeling`, with `num_labels` as the default number of vertex and edge labels.
+fn labeling_args<'a, 'b>(num_labels: Option<&'a str>) -> Vec<Arg<'a, 'b>> {
+    let num_labels_arg = |name| match num_labels {
+        Some(num_labels) => Arg::with_name(name).long(name).default_value(num_labels),
+        None => Arg::with_name(name).long(name).takes_value(true),
+    };
+    vec![
+        Arg::with_name("labeling")
+            .long("labeling")
+            .possible_values(&["uniform", "zipf", "degree", "frequencies", "copy"])
+            .default_value("uniform")
+            .help("Strategy to assign labels"),
+        num_labels_arg("num-vlabels")
+            .help("Number of vertex labels of uniform, zipf and degree labelings"),
+        num_labels_arg("num-elabels")
+            .help("Number of edge labels of uniform, zipf and degree labelings"),
+        Arg::with_name("skew")
+            .long("skew")
+            .default_value("1")
+            .help("Exponent of zipf labelings"),
+        Arg::with_name("vlabel-frequencies")
+            .long("vlabel-frequencies")
+            .takes_value(true)
+            .required_if("labeling", "frequencies")
+            .help("Draws vertex labels from a `vlabel weight` file"),
+        Arg::with_name("elabel-frequencies")
+            .long("elabel-frequencies")
+            .takes_value(true)
+            .required_if("labeling", "frequencies")
+            .help("Draws edge labels from an `elabel weight` file"),
+        Arg::with_name("labels-from")
+            .long("labels-from")
+            .takes_value(true)
+            .required_if("labeling", "copy")
+            .help("Copies labels from a SQLite3 data graph"),
+    ]
+}
+
+fn labeling(matches: &ArgMatches) -> Result<Labeling, Box<dyn Error>> {
+    let name = matches.value_of("labeling").unwrap();
+    let num_labels = |option: &'static str| -> Result<usize, Box<dyn Error>> {
+        Ok(matches
+            .value_of(option)
+            .ok_or_else(|| MissingOption {
+                option,
+                labeling: name.to_string(),
+            })?
+            .parse()?)
+    };
+    let frequencies = |option| {
+        read_frequencies(BufReader::new(File::open(
+            matches.value_of(option).unwrap(),
+        )?))
+    };
+    Ok(match name {
+        "uniform" => Labeling::Uniform {
+            num_vlabels: num_labels("num-vlabels")?,
+            num_elabels: num_labels("num-elabels")?,
+        },
+        "zipf" => Labeling::Zipf {
+            num_vlabels: num_labels("num-vlabels")?,
+            num_elabels: num_labels("num-elabels")?,
+            skew: matches.value_of("skew").unwrap().parse()?,
+        },
+        "degree" => Labeling::Degree {
+            num_vlabels: num_labels("num-vlabels")?,
+            num_elabels: num_labels("num-elabels")?,
+        },
+        "frequencies" => Labeling::Frequencies {
+            vlabels: frequencies("vlabel-frequencies")?,
+            elabels: frequencies("elabel-frequencies")?,
+        },
+        "copy" => {
+            Labeling::copy_from_sqlite3(&sqlite::open(matches.value_of("labels-from").unwrap())?)?
+        }
+        _ => unreachable!(),
+    })
+}
+
+fn handle_createbin(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+    match matches.value_of("FMT").unwrap() {
+        "snap_edges" => {
+            snap_edges_to_bin(
+                matches.value_of("INPUT").unwrap(),
+                &edge_list_options(matches)?,
+                &mut output,
             )?;
         }
         _ => unreachable!(),
@@ -36,6 +313,9 @@ fn handle_createdb(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
 
 fn handle_convertdb(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     let conn = sqlite::open(matches.value_of("SQLITE3").unwrap())?;
+    if matches.is_present("dense-ids") {
+        use_dense_vids(&conn)?;
+    }
     match matches.value_of("FMT").unwrap() {
         "graphflow" => {
             let (path, name) = split_path(matches.value_of("OUTPUT").unwrap())?;
@@ -53,15 +333,85 @@ fn handle_convertdb(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
                 BufWriter::new(File::create(path.join(format!("{}_edges.csv", name)))?);
             sqlite3_to_neo4j(&conn, &mut vertices_buf, &mut edges_buf)?;
         }
+        "datalog" => {
+            let path = Path::new(matches.value_of("OUTPUT").unwrap());
+            std::fs::create_dir_all(path)?;
+            let mut vertices_buf = BufWriter::new(File::create(path.join("vertex.facts"))?);
+            let mut edges_buf = BufWriter::new(File::create(path.join("edge.facts"))?);
+            sqlite3_to_datalog(&conn, &mut vertices_buf, &mut edges_buf)?;
+        }
+        "ntriples" => {
+            let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+            sqlite3_to_ntriples(&conn, &mut output)?;
+        }
+        "bin" => {
+            let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+            sqlite3_to_bin(&conn, &mut output)?;
+        }
+        "graph" => {
+            let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+            sqlite3_to_graph(&conn, &mut output)?;
+        }
+        "csr" => {
+            let (path, name) = split_path(matches.value_of("OUTPUT").unwrap())?;
+            let create = |suffix: &str| {
+                OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(path.join(format!("{}_{}.bin", name, suffix)))
+            };
+            let (offsets, neighbours, elabels) = (
+                create("offsets")?,
+                create("neighbours")?,
+                create("elabels")?,
+            );
+            let in_files = if matches.is_present("reverse") {
+                Some((
+                    create("in_offsets")?,
+                    create("in_neighbours")?,
+                    create("in_elabels")?,
+                ))
+            } else {
+                None
+            };
+            sqlite3_to_csr(
+                &conn,
+                &create("vlabels")?,
+                CsrFiles {
+                    offsets: &offsets,
+                    neighbours: &neighbours,
+                    elabels: &elabels,
+                },
+                in_files
+                    .as_ref()
+                    .map(|(offsets, neighbours, elabels)| CsrFiles {
+                        offsets,
+                        neighbours,
+                        elabels,
+                    }),
+            )?;
+        }
+        "mtx" => {
+            let (path, name) = split_path(matches.value_of("OUTPUT").unwrap())?;
+            let mut matrix_buf = BufWriter::new(File::create(path.join(format!("{}.mtx", name)))?);
+            let mut vlabels_buf =
+                BufWriter::new(File::create(path.join(format!("{}_vlabels.mtx", name)))?);
+            sqlite3_to_mtx(&conn, &mut matrix_buf, &mut vlabels_buf)?;
+        }
+        "graphml" => {
+            let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+            sqlite3_to_graphml(&conn, &mut output, &label_attributes(matches))?;
+        }
+        "gml" => {
+            let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+            sqlite3_to_gml(&conn, &mut output, &label_attributes(matches))?;
+        }
         "sqlite3" => {
             File::create(matches.value_of("OUTPUT").unwrap())?;
             let new_conn = sqlite::open(matches.value_of("OUTPUT").unwrap())?;
-            sqlite3_to_sqlite3(
-                &conn,
-                &new_conn,
-                matches.value_of("num-vlabels").unwrap().parse()?,
-                matches.value_of("num-elabels").unwrap().parse()?,
-            );
+            sqlite3_to_sqlite3(&conn, &new_conn, &labeling(matches)?)?;
         }
         _ => unreachable!(),
     }
@@ -85,19 +435,81 @@ fn handle_convertgisp(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     BufReader::new(File::open(matches.value_of("GISP").unwrap())?).read_to_string(&mut gisp)?;
     let ast = parse(&gisp)?;
     let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+    let semantics = matches.value_of("semantics").map(|_| semantics(matches));
     match matches.value_of("FMT").unwrap() {
-        "gisp" => writeln!(
-            &mut output,
-            "{}",
-            gisp_to_gisp(
-                &ast,
-                matches.value_of("num-vlabels").unwrap().parse()?,
-                matches.value_of("num-elabels").unwrap().parse()?
-            )
-        )?,
-        "graphflow" => writeln!(&mut output, "{}", gisp_to_graphflow(&ast))?,
-        "cypher" => writeln!(&mut output, "{}", gisp_to_cypher(&ast))?,
+        "gisp" => match matches.value_of("embed-in") {
+            Some(sqlite3) => {
+                let graph = Graph::from_sqlite3(&sqlite::open(sqlite3)?)?;
+                writeln!(&mut output, "{}", gisp_to_gisp_embedded(&ast, &graph)?)?
+            }
+            None => writeln!(&mut output, "{}", gisp_to_gisp(&ast, &labeling(matches)?)?)?,
+        },
+        "datalog" => writeln!(&mut output, "{}", gisp_to_datalog(&ast, semantics))?,
+        "graphflow" => writeln!(&mut output, "{}", gisp_to_graphflow(&ast, semantics)?)?,
+        "cypher" => writeln!(&mut output, "{}", gisp_to_cypher(&ast, semantics)?)?,
+        "sparql" => writeln!(&mut output, "{}", gisp_to_sparql(&ast, semantics))?,
+        "sql" => writeln!(&mut output, "{}", gisp_to_sql(&ast, semantics))?,
+        "graph" => writeln!(&mut output, "{}", gisp_to_graph(&ast)?)?,
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn handle_creategisp(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut input = String::new();
+    BufReader::new(File::open(matches.value_of("INPUT").unwrap())?).read_to_string(&mut input)?;
+    let ast = match matches.value_of("FMT").unwrap() {
+        "graph" => graph_to_gisp(&input)?,
+        _ => unreachable!(),
+    };
+    let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+    writeln!(&mut output, "{}", ast)?;
+    Ok(())
+}
+
+fn handle_breaksym(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut gisp = String::new();
+    BufReader::new(File::open(matches.value_of("GISP").unwrap())?).read_to_string(&mut gisp)?;
+    let ast = parse(&gisp)?;
+    let mut output = BufWriter::new(File::create(matches.value_of("OUTPUT").unwrap())?);
+    writeln!(&mut output, "{}", break_symmetry(&ast))?;
+    Ok(())
+}
+
+fn handle_genqueries(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let conn = sqlite::open(matches.value_of("SQLITE3").unwrap())?;
+    let outdir = Path::new(matches.value_of("OUTDIR").unwrap());
+    let num_vertices: usize = matches.value_of("num-vertices").unwrap().parse()?;
+    let shape = match matches.value_of("shape").unwrap() {
+        "tree" => Shape::Tree,
+        "cyclic" => Shape::Cyclic,
+        "dense" => Shape::Dense,
+        "sparse" => Shape::Sparse,
         _ => unreachable!(),
+    };
+    let sampling = match matches.value_of("sampling").unwrap() {
+        "walk" => Sampling::Walk,
+        "bfs" => Sampling::Bfs,
+        _ => unreachable!(),
+    };
+    let queries = sqlite3_to_gisp(
+        &conn,
+        matches.value_of("num-queries").unwrap().parse()?,
+        num_vertices,
+        shape,
+        sampling,
+    )?;
+    for (i, ast) in queries.iter().enumerate() {
+        writeln!(
+            &mut BufWriter::new(File::create(outdir.join(format!(
+                "{}_{}_{}.gisp",
+                matches.value_of("shape").unwrap(),
+                num_vertices,
+                i
+            )))?),
+            "{}",
+            ast
+        )?;
     }
     Ok(())
 }
@@ -106,10 +518,63 @@ fn handle_gispinfo(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     let gisp_path = Path::new(matches.value_of("GISP").unwrap());
     let mut gisp = String::new();
     BufReader::new(File::open(gisp_path)?).read_to_string(&mut gisp)?;
-    let ast = parse(&gisp)?;
-    println!("num_vertices: {}", ast.vertices().len());
-    println!("num_arcs: {}", ast.arcs().len());
-    println!("num_edges: {}", ast.edges().len());
+    let stats = stats(&parse(&gisp)?);
+    match matches.value_of("format").unwrap() {
+        "text" => println!("{}", stats),
+        "json" => println!("{}", stats.to_json()),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn handle_lint(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let target = match matches.value_of("target") {
+        Some("cypher") => Some(Target::Cypher),
+        Some("graphflow") => Some(Target::Graphflow),
+        None => None,
+        _ => unreachable!(),
+    };
+    let mut gisp_paths = Vec::new();
+    for path in matches.values_of("PATH").unwrap() {
+        collect_gisp_paths(Path::new(path), &mut gisp_paths)?;
+    }
+    let mut num_failed = 0;
+    for gisp_path in &gisp_paths {
+        let mut gisp = String::new();
+        BufReader::new(File::open(gisp_path)?).read_to_string(&mut gisp)?;
+        let diagnostics = lint(&gisp, target);
+        for diagnostic in &diagnostics {
+            println!("{}: {}", gisp_path.display(), diagnostic);
+        }
+        if !diagnostics.is_empty() {
+            num_failed += 1;
+        }
+    }
+    if num_failed > 0 {
+        Err(Box::new(LintFailed {
+            num_failed,
+            num_files: gisp_paths.len(),
+        }))
+    } else {
+        Ok(())
+    }
+}
+
+/// Collects `path` itself, or the `.gisp` files under it if it is a directory.
+fn collect_gisp_paths(path: &Path, gisp_paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension() == Some(OsStr::new("gisp")) {
+                collect_gisp_paths(&entry, gisp_paths)?;
+            }
+        }
+    } else {
+        gisp_paths.push(path.to_path_buf());
+    }
     Ok(())
 }
 
@@ -127,7 +592,7 @@ fn handle_stars(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
         .collect();
     for root in roots {
         writeln!(
-            &mut BufWriter::new(File::create(outdir.join(&format!(
+            &mut BufWriter::new(File::create(outdir.join(format!(
                 "{}_{}.{}",
                 gisp_path.file_stem().unwrap().to_string_lossy(),
                 root,
@@ -139,16 +604,9 @@ fn handle_stars(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
         match matches.value_of("method").unwrap() {
             "opgm" => {}
             "stwig" => {
-                ast.arcs = ast
-                    .arcs
-                    .into_iter()
-                    .filter(|&(src, dst, _)| src != root && dst != root)
-                    .collect();
-                ast.edges = ast
-                    .edges
-                    .into_iter()
-                    .filter(|&(src, dst, _)| src != root && dst != root)
-                    .collect();
+                ast.arcs.retain(|&(src, dst, _)| src != root && dst != root);
+                ast.edges
+                    .retain(|&(src, dst, _)| src != root && dst != root);
             }
             _ => unreachable!(),
         }
@@ -156,6 +614,58 @@ fn handle_stars(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn handle_match(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut gisp = String::new();
+    BufReader::new(File::open(matches.value_of("GISP").unwrap())?).read_to_string(&mut gisp)?;
+    let ast = parse(&gisp)?;
+    let conn = sqlite::open(matches.value_of("SQLITE3").unwrap())?;
+    let semantics = semantics(matches);
+    let graph = match matches.value_of("engine").unwrap() {
+        "backtracking" => Some(Graph::from_sqlite3(&conn)?),
+        "sql" => None,
+        _ => unreachable!(),
+    };
+    let find = |f: &mut dyn FnMut(&[VId])| -> Result<usize, Box<dyn Error>> {
+        Ok(match &graph {
+            Some(graph) => find_matches(&ast, graph, semantics, f),
+            None => find_sql_matches(&conn, &ast, Some(semantics), f)?,
+        })
+    };
+    let num_matches = match matches.value_of("csv") {
+        Some(path) => {
+            let mut output = BufWriter::new(File::create(path)?);
+            let header: Vec<String> = ast
+                .vertices()
+                .iter()
+                .map(|&(vid, _)| format!("u{}", vid))
+                .collect();
+            writeln!(&mut output, "{}", header.join(","))?;
+            let mut result = Ok(());
+            let num_matches = find(&mut |vids| {
+                if result.is_ok() {
+                    let row: Vec<String> = vids.iter().map(|vid| vid.to_string()).collect();
+                    result = writeln!(&mut output, "{}", row.join(","));
+                }
+            })?;
+            result?;
+            num_matches
+        }
+        None => find(&mut |_| ())?,
+    };
+    println!("{}", num_matches);
+    Ok(())
+}
+
+fn semantics(matches: &ArgMatches) -> Semantics {
+    match matches.value_of("semantics").unwrap() {
+        "homomorphism" => Semantics::Homomorphism,
+        "edge-isomorphism" => Semantics::EdgeIsomorphism,
+        "vertex-isomorphism" => Semantics::VertexIsomorphism,
+        "induced" => Semantics::Induced,
+        _ => unreachable!(),
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let matches = App::new(crate_name!())
         .version(crate_version!())
@@ -164,62 +674,230 @@ fn main() -> Result<(), Box<dyn Error>> {
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .subcommand(
             SubCommand::with_name("createdb")
-                .about("Creates SQLite3 file from other formats")
+                .about("Creates SQLite3 file from other formats or random models")
+                .setting(AppSettings::AllowMissingPositional)
+                .arg(Arg::with_name("FMT").required(true).possible_values(&[
+                    "snap_edges",
+                    "graph",
+                    "graphml",
+                    "gml",
+                    "bin",
+                    "erdos_renyi",
+                    "barabasi_albert",
+                    "rmat",
+                    "regular",
+                ]))
+                .arg(Arg::with_name("INPUT").required_ifs(&[
+                    ("FMT", "snap_edges"),
+                    ("FMT", "graph"),
+                    ("FMT", "graphml"),
+                    ("FMT", "gml"),
+                    ("FMT", "bin"),
+                ]))
+                .arg(Arg::with_name("SQLITE3").required(true))
+                .arg(
+                    Arg::with_name("num-vertices")
+                        .long("num-vertices")
+                        .takes_value(true)
+                        .required_ifs(&[
+                            ("FMT", "erdos_renyi"),
+                            ("FMT", "barabasi_albert"),
+                            ("FMT", "rmat"),
+                            ("FMT", "regular"),
+                        ]),
+                )
+                .arg(
+                    Arg::with_name("num-edges")
+                        .long("num-edges")
+                        .takes_value(true)
+                        .conflicts_with("density"),
+                )
+                .arg(
+                    Arg::with_name("density")
+                        .long("density")
+                        .takes_value(true)
+                        .required_unless_one(&["num-edges", "INPUT"]),
+                )
+                .arg(
+                    Arg::with_name("memory-budget")
+                        .long("memory-budget")
+                        .help("MiB of vertex ids kept in memory while importing snap_edges")
+                        .default_value("1024"),
+                )
+                .arg(Arg::with_name("progress").long("progress"))
+                .arg(
+                    Arg::with_name("vlabels")
+                        .long("vlabels")
+                        .takes_value(true)
+                        .help("Sets vertex labels from a `vid vlabel` file"),
+                )
+                .arg(
+                    Arg::with_name("elabels")
+                        .long("elabels")
+                        .takes_value(true)
+                        .help("Sets edge labels from a `src dst elabel` file"),
+                )
+                .arg(
+                    Arg::with_name("compact")
+                        .long("compact")
+                        .takes_value(true)
+                        .possible_values(&["degree", "bfs"])
+                        .help("Maps vertex ids to 0..n in the vid_map table"),
+                )
+                .args(&edge_list_args())
+                .args(&label_attribute_args())
+                .args(&labeling_args(Some("1"))),
+        )
+        .subcommand(
+            SubCommand::with_name("createbin")
+                .about("Creates binary data graph from other formats")
                 .arg(
                     Arg::with_name("FMT")
                         .required(true)
                         .possible_values(&["snap_edges"]),
                 )
                 .arg(Arg::with_name("INPUT").required(true))
-                .arg(Arg::with_name("SQLITE3").required(true)),
+                .arg(Arg::with_name("OUTPUT").required(true))
+                .args(&edge_list_args()),
         )
         .subcommand(
             SubCommand::with_name("convertdb")
                 .about("Converts SQLite3 file to other format")
                 .arg(Arg::with_name("FMT").required(true).possible_values(&[
+                    "bin",
+                    "csr",
+                    "datalog",
+                    "gml",
+                    "graph",
                     "graphflow",
+                    "graphml",
+                    "mtx",
                     "neo4j",
+                    "ntriples",
                     "sqlite3",
                 ]))
                 .arg(Arg::with_name("SQLITE3").required(true))
                 .arg(Arg::with_name("OUTPUT").required(true))
                 .arg(
-                    Arg::with_name("num-vlabels")
-                        .long("num-vlabels")
-                        .takes_value(true)
-                        .required_if("FMT", "sqlite3"),
+                    Arg::with_name("reverse")
+                        .long("reverse")
+                        .help("Also writes the csr of in-edges"),
                 )
                 .arg(
-                    Arg::with_name("num-elabels")
-                        .long("num-elabels")
-                        .takes_value(true)
-                        .required_if("FMT", "sqlite3"),
-                ),
+                    Arg::with_name("dense-ids")
+                        .long("dense-ids")
+                        .help("Writes the ids of the vid_map table created by createdb --compact"),
+                )
+                .args(&label_attribute_args())
+                .args(&labeling_args(None)),
         )
         .subcommand(
             SubCommand::with_name("convertgisp")
                 .about("Converts gisp file to other format")
                 .arg(Arg::with_name("FMT").required(true).possible_values(&[
                     "cypher",
+                    "datalog",
                     "gisp",
+                    "graph",
                     "graphflow",
+                    "sparql",
+                    "sql",
                 ]))
                 .arg(Arg::with_name("GISP").required(true))
                 .arg(Arg::with_name("OUTPUT").required(true))
+                .args(&labeling_args(None))
+                .arg(
+                    Arg::with_name("semantics")
+                        .long("semantics")
+                        .takes_value(true)
+                        .possible_values(&[
+                            "homomorphism",
+                            "edge-isomorphism",
+                            "vertex-isomorphism",
+                            "induced",
+                        ])
+                        .help(
+                            "Makes cypher, datalog, graphflow, sparql and sql queries compute these \
+                             matches instead of those of the engine",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("embed-in")
+                        .long("embed-in")
+                        .takes_value(true)
+                        .help(
+                            "Labels the pattern as a random embedding in a SQLite3 data graph, \
+                             instead of by --labeling, so that it has a match",
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("creategisp")
+                .about("Creates gisp file from other formats")
+                .arg(
+                    Arg::with_name("FMT")
+                        .required(true)
+                        .possible_values(&["graph"]),
+                )
+                .arg(Arg::with_name("INPUT").required(true))
+                .arg(Arg::with_name("OUTPUT").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("breaksym")
+                .about("Adds symmetry-breaking constraints to gisp file")
+                .arg(Arg::with_name("GISP").required(true))
+                .arg(Arg::with_name("OUTPUT").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("genqueries")
+                .about("Samples gisp files from SQLite3 file")
+                .arg(Arg::with_name("SQLITE3").required(true))
+                .arg(Arg::with_name("OUTDIR").required(true))
+                .arg(
+                    Arg::with_name("num-queries")
+                        .long("num-queries")
+                        .default_value("1"),
+                )
                 .arg(
-                    Arg::with_name("num-vlabels")
-                        .long("num-vlabels")
+                    Arg::with_name("num-vertices")
+                        .long("num-vertices")
                         .takes_value(true)
-                        .required_if("FMT", "gisp"),
+                        .required(true),
                 )
                 .arg(
-                    Arg::with_name("num-elabels")
-                        .long("num-elabels")
+                    Arg::with_name("shape")
+                        .long("shape")
+                        .default_value("tree")
+                        .possible_values(&["tree", "cyclic", "dense", "sparse"]),
+                )
+                .arg(
+                    Arg::with_name("sampling")
+                        .long("sampling")
+                        .default_value("walk")
+                        .possible_values(&["walk", "bfs"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("gispinfo")
+                .arg(Arg::with_name("GISP").required(true))
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .default_value("text")
+                        .possible_values(&["text", "json"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("Checks gisp files, or the gisp files under directories")
+                .arg(Arg::with_name("PATH").required(true).multiple(true))
+                .arg(
+                    Arg::with_name("target")
+                        .long("target")
                         .takes_value(true)
-                        .required_if("FMT", "gisp"),
+                        .possible_values(&["cypher", "graphflow"]),
                 ),
         )
-        .subcommand(SubCommand::with_name("gispinfo").arg(Arg::with_name("GISP").required(true)))
         .subcommand(
             SubCommand::with_name("stars")
                 .arg(Arg::with_name("GISP").required(true))
@@ -232,17 +910,59 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .possible_values(&["opgm", "stwig"]),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("match")
+                .about("Counts the matches of a gisp file in a SQLite3 data graph")
+                .arg(Arg::with_name("GISP").required(true))
+                .arg(Arg::with_name("SQLITE3").required(true))
+                .arg(
+                    Arg::with_name("semantics")
+                        .long("semantics")
+                        .default_value("homomorphism")
+                        .possible_values(&[
+                            "homomorphism",
+                            "edge-isomorphism",
+                            "vertex-isomorphism",
+                            "induced",
+                        ]),
+                )
+                .arg(
+                    Arg::with_name("engine")
+                        .long("engine")
+                        .default_value("backtracking")
+                        .possible_values(&["backtracking", "sql"])
+                        .help("Matches in memory, or runs the query of convertgisp sql"),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .takes_value(true)
+                        .help("Writes the data vertex ids of every match"),
+                ),
+        )
         .get_matches();
     if let Some(matches) = matches.subcommand_matches("createdb") {
         handle_createdb(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("createbin") {
+        handle_createbin(matches)?;
     } else if let Some(matches) = matches.subcommand_matches("convertdb") {
         handle_convertdb(matches)?;
     } else if let Some(matches) = matches.subcommand_matches("convertgisp") {
         handle_convertgisp(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("creategisp") {
+        handle_creategisp(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("breaksym") {
+        handle_breaksym(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("genqueries") {
+        handle_genqueries(matches)?;
     } else if let Some(matches) = matches.subcommand_matches("gispinfo") {
         handle_gispinfo(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("lint") {
+        handle_lint(matches)?;
     } else if let Some(matches) = matches.subcommand_matches("stars") {
         handle_stars(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("match") {
+        handle_match(matches)?;
     }
     Ok(())
 }
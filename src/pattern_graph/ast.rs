@@ -1,7 +1,8 @@
 //! Abstract Syntax Tree
 
 use crate::types::{ELabel, VId, VLabel};
-use derive_more::Display;
+use derive_more::{Display, Error};
+use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
 pub struct Ast {
@@ -72,7 +73,7 @@ impl std::fmt::Display for Ast {
             write!(
                 f,
                 " (edges {})",
-                self.arcs()
+                self.edges()
                     .iter()
                     .map(|&(src, dst, elabel)| format!("(u{} u{} {})", src, dst, elabel))
                     .collect::<Vec<_>>()
@@ -86,5 +87,161 @@ impl std::fmt::Display for Ast {
     }
 }
 
-#[derive(Debug, Display, Clone, PartialEq)]
-pub enum Expr {}
+/// Constraint of the `(where ...)` section.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    VId(VId),
+    VLabel(VId),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Cmp(CmpOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Returns the vertices referenced by the expression.
+    pub fn vids(&self) -> Vec<VId> {
+        let mut vids = Vec::new();
+        self.collect_vids(&mut vids);
+        vids
+    }
+
+    fn collect_vids(&self, vids: &mut Vec<VId>) {
+        match self {
+            Expr::Int(_) => (),
+            Expr::VId(vid) | Expr::VLabel(vid) => vids.push(*vid),
+            Expr::Not(expr) => expr.collect_vids(vids),
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter().for_each(|expr| expr.collect_vids(vids))
+            }
+            Expr::Cmp(_, lhs, rhs) => {
+                lhs.collect_vids(vids);
+                rhs.collect_vids(vids);
+            }
+        }
+    }
+
+    /// Drops the parts of the expression which reference vertices rejected by `keep`.
+    ///
+    /// Conjuncts are dropped one by one, any other expression is dropped as a whole.
+    pub fn retain<F: Fn(VId) -> bool>(&self, keep: &F) -> Option<Expr> {
+        match self {
+            Expr::And(exprs) => {
+                let mut exprs: Vec<Expr> =
+                    exprs.iter().filter_map(|expr| expr.retain(keep)).collect();
+                match exprs.len() {
+                    0 => None,
+                    1 => exprs.pop(),
+                    _ => Some(Expr::And(exprs)),
+                }
+            }
+            _ if self.vids().into_iter().all(keep) => Some(self.clone()),
+            _ => None,
+        }
+    }
+
+    /// Accepts the conjunctions of `(= (label uN) L)` where `L` is the label of `uN`, i.e. the
+    /// constraints implied by the pattern itself, for `target` formats without filters.
+    pub fn check_implied(
+        &self,
+        target: &'static str,
+        vid_vlabels: &HashMap<VId, VLabel>,
+    ) -> Result<(), UnsupportedExpr> {
+        match self {
+            Expr::And(exprs) => exprs
+                .iter()
+                .try_for_each(|expr| expr.check_implied(target, vid_vlabels)),
+            Expr::Cmp(CmpOp::Eq, lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::VLabel(vid), Expr::Int(vlabel)) | (Expr::Int(vlabel), Expr::VLabel(vid))
+                    if vid_vlabels.get(vid).map(|&l| l as i64) == Some(*vlabel) =>
+                {
+                    Ok(())
+                }
+                _ => Err(UnsupportedExpr::new(target, self)),
+            },
+            _ => Err(UnsupportedExpr::new(target, self)),
+        }
+    }
+
+    /// Evaluates the expression on a match, where `vid` and `vlabel` return the id and label of
+    /// the data vertex matched by a pattern vertex.  Booleans are 0 and 1.
+    pub fn eval<F: Fn(VId) -> i64, G: Fn(VId) -> i64>(&self, vid: &F, vlabel: &G) -> i64 {
+        match self {
+            Expr::Int(n) => *n,
+            Expr::VId(u) => vid(*u),
+            Expr::VLabel(u) => vlabel(*u),
+            Expr::Not(expr) => (expr.eval(vid, vlabel) == 0) as i64,
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.eval(vid, vlabel) != 0) as i64,
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.eval(vid, vlabel) != 0) as i64,
+            Expr::Cmp(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(vid, vlabel), rhs.eval(vid, vlabel));
+                let holds = match op {
+                    CmpOp::Eq => lhs == rhs,
+                    CmpOp::Ne => lhs != rhs,
+                    CmpOp::Lt => lhs < rhs,
+                    CmpOp::Le => lhs <= rhs,
+                    CmpOp::Gt => lhs > rhs,
+                    CmpOp::Ge => lhs >= rhs,
+                };
+                holds as i64
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Int(n) => write!(f, "{}", n),
+            Expr::VId(vid) => write!(f, "u{}", vid),
+            Expr::VLabel(vid) => write!(f, "(label u{})", vid),
+            Expr::Not(expr) => write!(f, "(not {})", expr),
+            Expr::And(exprs) => write!(f, "(and {})", join(exprs)),
+            Expr::Or(exprs) => write!(f, "(or {})", join(exprs)),
+            Expr::Cmp(op, lhs, rhs) => write!(f, "({} {} {})", op, lhs, rhs),
+        }
+    }
+}
+
+fn join(exprs: &[Expr]) -> String {
+    exprs
+        .iter()
+        .map(|expr| expr.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Comparison operators.
+#[derive(Debug, Display, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    #[display(fmt = "=")]
+    Eq,
+    #[display(fmt = "!=")]
+    Ne,
+    #[display(fmt = "<")]
+    Lt,
+    #[display(fmt = "<=")]
+    Le,
+    #[display(fmt = ">")]
+    Gt,
+    #[display(fmt = ">=")]
+    Ge,
+}
+
+/// Error of a translator which cannot express a constraint.
+#[derive(Debug, Display, Error, PartialEq)]
+#[display(fmt = "{} cannot express `{}`", target, expr)]
+pub struct UnsupportedExpr {
+    pub target: &'static str,
+    pub expr: Expr,
+}
+
+impl UnsupportedExpr {
+    pub fn new(target: &'static str, expr: &Expr) -> Self {
+        Self {
+            target,
+            expr: expr.clone(),
+        }
+    }
+}
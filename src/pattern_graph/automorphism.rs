@@ -0,0 +1,264 @@
+//! Automorphisms of patterns
+
+use crate::{
+    pattern_graph::{Ast, CmpOp, Expr},
+    types::{ELabel, VId},
+};
+use std::collections::HashMap;
+
+/// Returns every automorphism as the images of `ast.vertices()`.
+///
+/// Automorphisms preserve vertex labels, edge labels and arc directions.
+pub fn automorphisms(ast: &Ast) -> Vec<Vec<VId>> {
+    let mut results = Vec::new();
+    Search::new(ast)
+        .run(&mut |images| results.push(images.iter().map(|&i| ast.vertices()[i].0).collect()));
+    results
+}
+
+pub fn count_automorphisms(ast: &Ast) -> usize {
+    let mut count = 0;
+    Search::new(ast).run(&mut |_| count += 1);
+    count
+}
+
+/// Returns `(uA, uB)` pairs such that requiring `uA < uB` for all of them keeps exactly one
+/// embedding out of each class of embeddings equivalent under automorphisms.
+///
+/// Follows Grochow and Kellis: the vertex of the largest orbit is made smaller than the rest of
+/// its orbit, and the search goes on with its stabilizer until no automorphism is left.
+///
+/// Only the automorphisms mapping the `where` section onto itself are broken, as the others do
+/// not map embeddings to embeddings.  The constraints assume distinct pattern vertices are
+/// matched to distinct data vertices, so they drop embeddings under homomorphisms and edge
+/// isomorphisms.
+pub fn symmetry_breaking(ast: &Ast) -> Vec<(VId, VId)> {
+    let mut group = automorphisms(ast);
+    if let Some(expr) = ast.constraint() {
+        let canonical = normalize(expr, &|vid| vid);
+        group.retain(|images| {
+            let image: HashMap<VId, VId> = ast
+                .vertices()
+                .iter()
+                .map(|&(vid, _)| vid)
+                .zip(images.iter().cloned())
+                .collect();
+            normalize(expr, &|vid| image.get(&vid).cloned().unwrap_or(vid)) == canonical
+        });
+    }
+    let mut constraints = Vec::new();
+    while group.len() > 1 {
+        let orbits: Vec<Vec<VId>> = (0..ast.vertices().len())
+            .map(|i| {
+                let mut orbit: Vec<VId> = group.iter().map(|images| images[i]).collect();
+                orbit.sort_unstable();
+                orbit.dedup();
+                orbit
+            })
+            .collect();
+        let i = (0..orbits.len())
+            .max_by_key(|&i| (orbits[i].len(), std::cmp::Reverse(i)))
+            .unwrap();
+        let vid = ast.vertices()[i].0;
+        for &other in &orbits[i] {
+            if other != vid {
+                constraints.push((vid, other));
+            }
+        }
+        group.retain(|images| images[i] == vid);
+    }
+    constraints
+}
+
+/// Renames the vertices of the expression, and sorts the operands of `and` and `or` so that
+/// conjunctions and disjunctions equal up to their order compare equal.
+fn normalize<F: Fn(VId) -> VId>(expr: &Expr, rename: &F) -> Expr {
+    let normalize_all = |exprs: &[Expr]| {
+        let mut exprs: Vec<Expr> = exprs.iter().map(|expr| normalize(expr, rename)).collect();
+        exprs.sort_by_cached_key(|expr| expr.to_string());
+        exprs
+    };
+    match expr {
+        Expr::Int(n) => Expr::Int(*n),
+        Expr::VId(vid) => Expr::VId(rename(*vid)),
+        Expr::VLabel(vid) => Expr::VLabel(rename(*vid)),
+        Expr::Not(expr) => Expr::Not(Box::new(normalize(expr, rename))),
+        Expr::And(exprs) => Expr::And(normalize_all(exprs)),
+        Expr::Or(exprs) => Expr::Or(normalize_all(exprs)),
+        Expr::Cmp(op, lhs, rhs) => Expr::Cmp(
+            *op,
+            Box::new(normalize(lhs, rename)),
+            Box::new(normalize(rhs, rename)),
+        ),
+    }
+}
+
+/// Adds the symmetry-breaking constraints to the `where` section of the pattern.
+///
+/// The result is only equivalent to the pattern under vertex isomorphisms and induced
+/// semantics, see `symmetry_breaking`.
+pub fn break_symmetry(ast: &Ast) -> Ast {
+    let mut exprs: Vec<Expr> = match ast.constraint() {
+        Some(Expr::And(exprs)) => exprs.clone(),
+        Some(expr) => vec![expr.clone()],
+        None => vec![],
+    };
+    exprs.extend(symmetry_breaking(ast).into_iter().map(|(lhs, rhs)| {
+        Expr::Cmp(
+            CmpOp::Lt,
+            Box::new(Expr::VId(lhs)),
+            Box::new(Expr::VId(rhs)),
+        )
+    }));
+    let constraint = match exprs.len() {
+        0 => None,
+        1 => exprs.pop(),
+        _ => Some(Expr::And(exprs)),
+    };
+    Ast::new(
+        ast.vertices().to_vec(),
+        ast.arcs().to_vec(),
+        ast.edges().to_vec(),
+        constraint,
+    )
+}
+
+struct Search<'a> {
+    ast: &'a Ast,
+    arcs: HashMap<(usize, usize), Vec<ELabel>>,
+    edges: HashMap<(usize, usize), Vec<ELabel>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(ast: &'a Ast) -> Self {
+        let indices: HashMap<VId, usize> = ast
+            .vertices()
+            .iter()
+            .enumerate()
+            .map(|(i, &(vid, _))| (vid, i))
+            .collect();
+        let (mut arcs, mut edges) = (HashMap::new(), HashMap::new());
+        // Arcs and edges with an undeclared vertex, which `validate` reports, are ignored.
+        let link = |&(src, dst, elabel): &(VId, VId, ELabel)| {
+            Some((*indices.get(&src)?, *indices.get(&dst)?, elabel))
+        };
+        for (src, dst, elabel) in ast.arcs().iter().filter_map(link) {
+            arcs.entry((src, dst)).or_insert_with(Vec::new).push(elabel);
+        }
+        for (src, dst, elabel) in ast.edges().iter().filter_map(link) {
+            edges
+                .entry((src.min(dst), src.max(dst)))
+                .or_insert_with(Vec::new)
+                .push(elabel);
+        }
+        arcs.values_mut()
+            .for_each(|elabels| elabels.sort_unstable());
+        edges
+            .values_mut()
+            .for_each(|elabels| elabels.sort_unstable());
+        Self { ast, arcs, edges }
+    }
+
+    fn run(&self, f: &mut dyn FnMut(&[usize])) {
+        let n = self.ast.vertices().len();
+        self.extend(&mut Vec::with_capacity(n), &mut vec![false; n], f);
+    }
+
+    fn extend(&self, images: &mut Vec<usize>, used: &mut [bool], f: &mut dyn FnMut(&[usize])) {
+        let i = images.len();
+        if i == used.len() {
+            return f(images);
+        }
+        for j in 0..used.len() {
+            if used[j] || self.ast.vertices()[i].1 != self.ast.vertices()[j].1 {
+                continue;
+            }
+            images.push(j);
+            if (0..=i).all(|k| self.preserves(i, k, images)) {
+                used[j] = true;
+                self.extend(images, used, f);
+                used[j] = false;
+            }
+            images.pop();
+        }
+    }
+
+    /// Checks the arcs and edges between `u` and `v` are mapped onto the same ones.
+    fn preserves(&self, u: usize, v: usize, images: &[usize]) -> bool {
+        let (fu, fv) = (images[u], images[v]);
+        self.arcs.get(&(u, v)) == self.arcs.get(&(fu, fv))
+            && self.arcs.get(&(v, u)) == self.arcs.get(&(fv, fu))
+            && self.edges.get(&(u.min(v), u.max(v))) == self.edges.get(&(fu.min(fv), fu.max(fv)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::parse;
+
+    #[test]
+    fn test_automorphisms() {
+        let square = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0) (u4 0)) \
+             (edges (u1 u2 0) (u2 u3 0) (u3 u4 0) (u4 u1 0)))",
+        )
+        .unwrap();
+        assert_eq!(count_automorphisms(&square), 8);
+        let cycle =
+            parse("(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u2 u3 0) (u3 u1 0)))")
+                .unwrap();
+        assert_eq!(
+            automorphisms(&cycle),
+            vec![vec![1, 2, 3], vec![2, 3, 1], vec![3, 1, 2]]
+        );
+        let labelled =
+            parse("(match (vertices (u1 0) (u2 0) (u3 1)) (edges (u1 u2 0) (u2 u3 0) (u3 u1 1)))")
+                .unwrap();
+        assert_eq!(count_automorphisms(&labelled), 1);
+    }
+
+    #[test]
+    fn test_symmetry_breaking() {
+        let square = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0) (u4 0)) \
+             (edges (u1 u2 0) (u2 u3 0) (u3 u4 0) (u4 u1 0)))",
+        )
+        .unwrap();
+        assert_eq!(
+            symmetry_breaking(&square),
+            vec![(1, 2), (1, 3), (1, 4), (2, 4)]
+        );
+        let star = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u1 u3 0)) \
+             (where (= (label u1) 0)))",
+        )
+        .unwrap();
+        assert_eq!(
+            break_symmetry(&star).to_string(),
+            "(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u1 u3 0)) \
+             (where (and (= (label u1) 0) (< u2 u3))))"
+        );
+    }
+
+    #[test]
+    fn test_symmetry_breaking_where() {
+        let path = |constraint: &str| {
+            parse(&format!(
+                "(match (vertices (u1 0) (u2 0) (u3 0)) (edges (u1 u2 0) (u2 u3 0)) (where {}))",
+                constraint
+            ))
+            .unwrap()
+        };
+        assert_eq!(symmetry_breaking(&path("(< u1 5)")), vec![]);
+        assert_eq!(symmetry_breaking(&path("(< u2 5)")), vec![(1, 3)]);
+        assert_eq!(
+            symmetry_breaking(&path("(and (< u1 5) (< u3 5))")),
+            vec![(1, 3)]
+        );
+        assert_eq!(
+            symmetry_breaking(&path("(or (= (label u3) 1) (< u1 u3))")),
+            vec![]
+        );
+    }
+}
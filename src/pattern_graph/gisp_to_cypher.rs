@@ -1,7 +1,19 @@
-use crate::pattern_graph::Ast;
-use std::collections::HashMap;
+use crate::{
+    pattern_graph::{Ast, CmpOp, Expr, Semantics, UnsupportedExpr},
+    types::{VId, VLabel},
+};
+use std::collections::{HashMap, HashSet};
 
-pub fn gisp_to_cypher(ast: &Ast) -> String {
+/// Translates the pattern to a Cypher query.
+///
+/// Cypher maps the relationships of a `MATCH` clause to distinct relationships, i.e. computes
+/// edge isomorphisms, which `semantics` may override: homomorphisms put every relationship in
+/// its own `MATCH` clause, while vertex isomorphisms and induced subgraphs add `uA <> uB` and
+/// `NOT (uA)--(uB)` predicates.
+///
+/// Cypher returns one row per relationship of each match, so its counts are those of
+/// `count_arc_matches` rather than `count_matches` on multigraphs and antiparallel arcs.
+pub fn gisp_to_cypher(ast: &Ast, semantics: Option<Semantics>) -> Result<String, UnsupportedExpr> {
     let vid_vlabels: HashMap<_, _> = ast
         .vertices()
         .iter()
@@ -12,19 +24,190 @@ pub fn gisp_to_cypher(ast: &Ast) -> String {
         .iter()
         .map(|&(vid, _)| format!("ID(u{})", vid))
         .collect();
-    let arcs: Vec<String> = ast
-        .arcs()
+    let mut bound = HashSet::with_capacity(ast.vertices().len());
+    let mut patterns = Vec::with_capacity(ast.arcs().len() + ast.edges().len());
+    for &(src, dst, elabel) in ast.arcs() {
+        patterns.push(format!(
+            "{}-[:`{}`]->{}",
+            node_to_cypher(src, &vid_vlabels, &mut bound),
+            elabel,
+            node_to_cypher(dst, &vid_vlabels, &mut bound)
+        ));
+    }
+    for &(src, dst, elabel) in ast.edges() {
+        patterns.push(format!(
+            "{}-[:`{}`]-{}",
+            node_to_cypher(src, &vid_vlabels, &mut bound),
+            elabel,
+            node_to_cypher(dst, &vid_vlabels, &mut bound)
+        ));
+    }
+    for &(vid, _) in ast.vertices() {
+        if !bound.contains(&vid) {
+            patterns.push(node_to_cypher(vid, &vid_vlabels, &mut bound));
+        }
+    }
+    let semantics = semantics.unwrap_or(Semantics::EdgeIsomorphism);
+    let mut predicates = Vec::new();
+    if let Some(expr) = ast.constraint() {
+        predicates.push(predicate_to_cypher(expr)?);
+    }
+    for (lhs, rhs) in semantics.distinct_pairs(ast) {
+        predicates.push(format!("u{} <> u{}", lhs, rhs));
+    }
+    for (lhs, rhs) in semantics.non_adjacent_pairs(ast) {
+        predicates.push(format!("NOT (u{})--(u{})", lhs, rhs));
+    }
+    let constraint = match predicates.is_empty() {
+        true => String::new(),
+        false => format!(" WHERE {}", predicates.join(" AND ")),
+    };
+    let separator = match semantics {
+        Semantics::Homomorphism => " MATCH ",
+        _ => ", ",
+    };
+    Ok(format!(
+        "MATCH {}{} RETURN {}",
+        patterns.join(separator),
+        constraint,
+        vertices.join(", ")
+    ))
+}
+
+/// Binds the label of a vertex only on its first occurrence.
+fn node_to_cypher(
+    vid: VId,
+    vid_vlabels: &HashMap<VId, VLabel>,
+    bound: &mut HashSet<VId>,
+) -> String {
+    if bound.insert(vid) {
+        format!("(u{}:`{}`)", vid, vid_vlabels.get(&vid).unwrap())
+    } else {
+        format!("(u{})", vid)
+    }
+}
+
+fn predicate_to_cypher(expr: &Expr) -> Result<String, UnsupportedExpr> {
+    match expr {
+        Expr::Not(expr) => Ok(format!("NOT ({})", predicate_to_cypher(expr)?)),
+        Expr::And(exprs) => Ok(format!("({})", predicates_to_cypher(exprs, " AND ")?)),
+        Expr::Or(exprs) => Ok(format!("({})", predicates_to_cypher(exprs, " OR ")?)),
+        Expr::Cmp(op, lhs, rhs) => match (op, lhs.as_ref(), rhs.as_ref()) {
+            (CmpOp::Eq, Expr::VLabel(vid), Expr::Int(vlabel))
+            | (CmpOp::Eq, Expr::Int(vlabel), Expr::VLabel(vid)) => {
+                Ok(format!("'{}' IN labels(u{})", vlabel, vid))
+            }
+            (CmpOp::Ne, Expr::VLabel(vid), Expr::Int(vlabel))
+            | (CmpOp::Ne, Expr::Int(vlabel), Expr::VLabel(vid)) => {
+                Ok(format!("NOT '{}' IN labels(u{})", vlabel, vid))
+            }
+            (CmpOp::Eq, Expr::VLabel(lhs), Expr::VLabel(rhs)) => {
+                Ok(format!("labels(u{}) = labels(u{})", lhs, rhs))
+            }
+            (CmpOp::Ne, Expr::VLabel(lhs), Expr::VLabel(rhs)) => {
+                Ok(format!("labels(u{}) <> labels(u{})", lhs, rhs))
+            }
+            (_, Expr::VLabel(_), _) | (_, _, Expr::VLabel(_)) => {
+                Err(UnsupportedExpr::new("cypher", expr))
+            }
+            (op, lhs, rhs) => Ok(format!(
+                "{} {} {}",
+                value_to_cypher(lhs)?,
+                match op {
+                    CmpOp::Ne => "<>".to_string(),
+                    op => op.to_string(),
+                },
+                value_to_cypher(rhs)?
+            )),
+        },
+        _ => Err(UnsupportedExpr::new("cypher", expr)),
+    }
+}
+
+fn predicates_to_cypher(exprs: &[Expr], sep: &str) -> Result<String, UnsupportedExpr> {
+    Ok(exprs
         .iter()
-        .map(|&(src, dst, elabel)| {
-            format!(
-                "(u{}:`{}`)-[:`{}`]->(u{}:`{}`)",
-                src,
-                vid_vlabels.get(&src).unwrap(),
-                elabel,
-                dst,
-                vid_vlabels.get(&dst).unwrap()
+        .map(predicate_to_cypher)
+        .collect::<Result<Vec<_>, _>>()?
+        .join(sep))
+}
+
+fn value_to_cypher(expr: &Expr) -> Result<String, UnsupportedExpr> {
+    match expr {
+        Expr::Int(n) => Ok(n.to_string()),
+        Expr::VId(vid) => Ok(format!("ID(u{})", vid)),
+        _ => Err(UnsupportedExpr::new("cypher", expr)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::parse;
+
+    #[test]
+    fn test_gisp_to_cypher() {
+        assert_eq!(
+            gisp_to_cypher(
+                &parse(
+                    "(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 12) (u1 u3 13)) \
+                     (where (and (< u2 u3) (or (= (label u1) 1) (not (!= u2 7))))))"
+                )
+                .unwrap(),
+                None
+            ),
+            Ok("MATCH (u1:`1`)-[:`12`]->(u2:`2`), (u1)-[:`13`]->(u3:`3`) \
+                WHERE (ID(u2) < ID(u3) AND ('1' IN labels(u1) OR NOT (ID(u2) <> 7))) \
+                RETURN ID(u1), ID(u2), ID(u3)"
+                .to_string())
+        );
+        assert!(gisp_to_cypher(
+            &parse("(match (vertices (u1 1) (u2 2)) (arcs (u1 u2 12)) (where (< (label u1) 2)))")
+                .unwrap(),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_gisp_to_cypher_edges() {
+        assert_eq!(
+            gisp_to_cypher(
+                &parse(
+                    "(match (vertices (u1 1) (u2 2) (u3 3) (u4 4)) \
+                     (arcs (u1 u2 12)) (edges (u2 u3 23) (u3 u1 31)))"
+                )
+                .unwrap(),
+                None
+            ),
+            Ok(
+                "MATCH (u1:`1`)-[:`12`]->(u2:`2`), (u2)-[:`23`]-(u3:`3`), (u3)-[:`31`]-(u1), \
+                 (u4:`4`) RETURN ID(u1), ID(u2), ID(u3), ID(u4)"
+                    .to_string()
             )
-        })
-        .collect();
-    format!("MATCH {} RETURN {}", arcs.join(", "), vertices.join(", "))
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_cypher_semantics() {
+        let ast = parse(
+            "(match (vertices (u1 1) (u2 1) (u3 2)) (arcs (u1 u2 0) (u2 u3 0)) \
+             (where (< u1 5)))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_cypher(&ast, Some(Semantics::Homomorphism)),
+            Ok(
+                "MATCH (u1:`1`)-[:`0`]->(u2:`1`) MATCH (u2)-[:`0`]->(u3:`2`) \
+                WHERE ID(u1) < 5 RETURN ID(u1), ID(u2), ID(u3)"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            gisp_to_cypher(&ast, Some(Semantics::Induced)),
+            Ok("MATCH (u1:`1`)-[:`0`]->(u2:`1`), (u2)-[:`0`]->(u3:`2`) \
+                WHERE ID(u1) < 5 AND u1 <> u2 AND NOT (u1)--(u3) RETURN ID(u1), ID(u2), ID(u3)"
+                .to_string())
+        );
+    }
 }
@@ -0,0 +1,150 @@
+use crate::{
+    pattern_graph::{Ast, Expr, Semantics},
+    types::{ELabel, VId, VLabel},
+};
+use std::collections::HashMap;
+
+/// Translates the pattern to a Soufflé program whose single rule derives the matches of
+/// `ast.vertices()` into the `pattern` relation, from the `vertex(id, label)` and
+/// `edge(src, dst, label)` relations of `data_graph::sqlite3_to_datalog`.
+///
+/// Undirected edges are disjunctions of both directions, and relations are sets, so matches are
+/// distinct.  Rules compute homomorphisms, so constraints are added for `semantics`.  Labels in
+/// the constraint are those of the pattern.
+pub fn gisp_to_datalog(ast: &Ast, semantics: Option<Semantics>) -> String {
+    let semantics = semantics.unwrap_or(Semantics::Homomorphism);
+    let vid_vlabels: HashMap<VId, VLabel> = ast.vertices().iter().cloned().collect();
+    let mut body: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, vlabel)| format!("vertex(u{}, {})", vid, vlabel))
+        .collect();
+    for &(src, dst, elabel) in ast.arcs() {
+        body.push(format!("edge(u{}, u{}, {})", src, dst, elabel));
+    }
+    // Ends of the data arcs matched by the arcs and edges, to keep them distinct.
+    let mut ends: Vec<(String, String, ELabel)> = ast
+        .arcs()
+        .iter()
+        .map(|&(src, dst, elabel)| (format!("u{}", src), format!("u{}", dst), elabel))
+        .collect();
+    for (i, &(src, dst, elabel)) in ast.edges().iter().enumerate() {
+        if semantics == Semantics::EdgeIsomorphism {
+            let (e_src, e_dst) = (format!("e{}_src", i + 1), format!("e{}_dst", i + 1));
+            body.push(format!(
+                "((edge(u{0}, u{1}, {2}), {3} = u{0}, {4} = u{1}); \
+                 (edge(u{1}, u{0}, {2}), {3} = u{1}, {4} = u{0}))",
+                src, dst, elabel, e_src, e_dst
+            ));
+            ends.push((e_src, e_dst, elabel));
+        } else {
+            body.push(format!(
+                "(edge(u{0}, u{1}, {2}); edge(u{1}, u{0}, {2}))",
+                src, dst, elabel
+            ));
+        }
+    }
+    if let Some(expr) = ast.constraint() {
+        body.push(expr_to_datalog(expr, &vid_vlabels));
+    }
+    if semantics == Semantics::EdgeIsomorphism {
+        for (i, (src, dst, elabel)) in ends.iter().enumerate() {
+            for (other_src, other_dst, other_elabel) in &ends[i + 1..] {
+                if elabel == other_elabel {
+                    body.push(format!(
+                        "({} != {}; {} != {})",
+                        src, other_src, dst, other_dst
+                    ));
+                }
+            }
+        }
+    }
+    for (lhs, rhs) in semantics.distinct_pairs(ast) {
+        body.push(format!("u{} != u{}", lhs, rhs));
+    }
+    for (lhs, rhs) in semantics.non_adjacent_pairs(ast) {
+        body.push(format!(
+            "!edge(u{0}, u{1}, _), !edge(u{1}, u{0}, _)",
+            lhs, rhs
+        ));
+    }
+    let vars: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, _)| format!("u{}", vid))
+        .collect();
+    let attributes: Vec<String> = vars.iter().map(|var| format!("{}: number", var)).collect();
+    format!(
+        ".decl vertex(id: number, label: number)\n\
+         .input vertex\n\
+         .decl edge(src: number, dst: number, label: number)\n\
+         .input edge\n\
+         .decl pattern({})\n\
+         .output pattern\n\
+         pattern({}) :- {}.",
+        attributes.join(", "),
+        vars.join(", "),
+        body.join(", ")
+    )
+}
+
+fn expr_to_datalog(expr: &Expr, vid_vlabels: &HashMap<VId, VLabel>) -> String {
+    match expr {
+        Expr::Int(n) => n.to_string(),
+        Expr::VId(vid) => format!("u{}", vid),
+        Expr::VLabel(vid) => vid_vlabels[vid].to_string(),
+        Expr::Not(expr) => format!("!({})", expr_to_datalog(expr, vid_vlabels)),
+        Expr::And(exprs) => format!("({})", exprs_to_datalog(exprs, ", ", vid_vlabels)),
+        Expr::Or(exprs) => format!("({})", exprs_to_datalog(exprs, "; ", vid_vlabels)),
+        Expr::Cmp(op, lhs, rhs) => format!(
+            "{} {} {}",
+            expr_to_datalog(lhs, vid_vlabels),
+            op,
+            expr_to_datalog(rhs, vid_vlabels)
+        ),
+    }
+}
+
+fn exprs_to_datalog(exprs: &[Expr], sep: &str, vid_vlabels: &HashMap<VId, VLabel>) -> String {
+    exprs
+        .iter()
+        .map(|expr| expr_to_datalog(expr, vid_vlabels))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::parse;
+
+    #[test]
+    fn test_gisp_to_datalog() {
+        let ast = parse(
+            "(match (vertices (u1 1) (u2 1) (u3 2)) (arcs (u1 u2 0)) (edges (u2 u3 0)) \
+             (where (or (< u1 u3) (not (= (label u1) 1)))))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_datalog(&ast, Some(Semantics::Induced)),
+            ".decl vertex(id: number, label: number)\n\
+             .input vertex\n\
+             .decl edge(src: number, dst: number, label: number)\n\
+             .input edge\n\
+             .decl pattern(u1: number, u2: number, u3: number)\n\
+             .output pattern\n\
+             pattern(u1, u2, u3) :- vertex(u1, 1), vertex(u2, 1), vertex(u3, 2), \
+             edge(u1, u2, 0), (edge(u2, u3, 0); edge(u3, u2, 0)), (u1 < u3; !(1 = 1)), \
+             u1 != u2, !edge(u1, u3, _), !edge(u3, u1, _)."
+        );
+        let ast =
+            parse("(match (vertices (u1 1) (u2 1)) (arcs (u1 u2 0)) (edges (u2 u1 0)))").unwrap();
+        assert!(
+            gisp_to_datalog(&ast, Some(Semantics::EdgeIsomorphism)).ends_with(
+                ":- vertex(u1, 1), vertex(u2, 1), edge(u1, u2, 0), \
+             ((edge(u2, u1, 0), e1_src = u2, e1_dst = u1); \
+             (edge(u1, u2, 0), e1_src = u1, e1_dst = u2)), (u1 != e1_src; u2 != e1_dst)."
+            )
+        );
+    }
+}
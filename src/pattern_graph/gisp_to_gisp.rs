@@ -1,29 +1,310 @@
 use crate::{
+    data_graph::{Graph, Labeling, NoLabels},
     pattern_graph::Ast,
     types::{ELabel, VId, VLabel},
     SEED,
 };
-use rand::{Rng, SeedableRng};
-use std::collections::HashSet;
+use derive_more::{Display, Error};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
+
+/// Number of data vertices tried before giving up on an embedding.
+const MAX_STEPS: usize = 1_000_000;
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum EmbeddingError {
+    #[display(fmt = "no embedding of the pattern found in the data graph")]
+    NotFound,
+    #[display(
+        fmt = "search limit of {} steps reached before finding an embedding",
+        MAX_STEPS
+    )]
+    SearchLimitReached,
+}
 
-pub fn gisp_to_gisp(ast: &Ast, num_vlabels: usize, num_elabels: usize) -> String {
+/// Assigns new labels to the pattern by `labeling`, where the degree of a vertex counts its arcs
+/// and edges.
+///
+/// Pattern vertices are not data vertices, so `Labeling::Copy` draws every label from the
+/// frequencies of the copied data graph.
+pub fn gisp_to_gisp(ast: &Ast, labeling: &Labeling) -> Result<String, NoLabels> {
     let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
-    Ast::new(
+    let mut degrees = HashMap::with_capacity(ast.vertices().len());
+    for &(src, dst, _) in ast.arcs().iter().chain(ast.edges()) {
+        *degrees.entry(src).or_insert(0) += 1;
+        *degrees.entry(dst).or_insert(0) += 1;
+    }
+    let degree = |vid| degrees.get(&vid).cloned().unwrap_or(0);
+    let num_links = ast.arcs().len() + ast.edges().len();
+    let (vlabels, elabels) = match *labeling {
+        // Drawn from ranges of `VLabel` and `ELabel`, as by earlier versions, so that the same
+        // seed keeps relabelling patterns the same way.
+        Labeling::Uniform {
+            num_vlabels,
+            num_elabels,
+        } => {
+            labeling.vlabel_draw()?;
+            labeling.elabel_draw()?;
+            let num_vlabels = VLabel::try_from(num_vlabels).unwrap_or(VLabel::MAX);
+            let num_elabels = ELabel::try_from(num_elabels).unwrap_or(ELabel::MAX);
+            let vlabels: Vec<VLabel> = ast
+                .vertices()
+                .iter()
+                .map(|_| rng.gen_range(0..num_vlabels))
+                .collect();
+            let elabels: Vec<ELabel> = (0..num_links)
+                .map(|_| rng.gen_range(0..num_elabels))
+                .collect();
+            (vlabels, elabels)
+        }
+        _ => (
+            labeling.vlabels(
+                None,
+                &ast.vertices()
+                    .iter()
+                    .map(|&(vid, _)| degree(vid))
+                    .collect::<Vec<_>>(),
+                &mut rng,
+            )?,
+            labeling.elabels(
+                None,
+                &ast.arcs()
+                    .iter()
+                    .chain(ast.edges())
+                    .map(|&(src, dst, _)| degree(src) + degree(dst))
+                    .collect::<Vec<_>>(),
+                &mut rng,
+            )?,
+        ),
+    };
+    let mut elabels = elabels.into_iter();
+    Ok(Ast::new(
         ast.vertices()
             .iter()
-            .map(|&(vid, _)| (vid, rng.gen_range(0..num_vlabels as VLabel)))
+            .zip(vlabels)
+            .map(|(&(vid, _), vlabel)| (vid, vlabel))
             .collect(),
         ast.arcs()
             .iter()
-            .map(|&(src, dst, _)| (src, dst, rng.gen_range(0..num_elabels as ELabel)))
+            .map(|&(src, dst, _)| (src, dst, elabels.next().unwrap()))
             .collect(),
         ast.edges()
             .iter()
-            .map(|&(src, dst, _)| (src, dst, rng.gen_range(0..num_elabels as ELabel)))
+            .map(|&(src, dst, _)| (src, dst, elabels.next().unwrap()))
             .collect(),
-        ast.constraint().map(|expr| expr.clone()),
+        ast.constraint().cloned(),
     )
-    .to_string()
+    .to_string())
+}
+
+/// Assigns to the pattern the labels of a random embedding in the data graph, so that it has
+/// at least one match.
+///
+/// The embedding maps distinct pattern vertices to distinct data vertices, follows the
+/// directions of arcs and satisfies the constraint with the ids of the data vertices.
+pub fn gisp_to_gisp_embedded(ast: &Ast, graph: &Graph) -> Result<String, EmbeddingError> {
+    let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
+    let order = embedding_order(ast);
+    let (mut mapping, mut steps) = (HashMap::with_capacity(order.len()), MAX_STEPS);
+    if !embed(ast, graph, &order, &mut mapping, &mut steps, &mut rng)? {
+        return Err(EmbeddingError::NotFound);
+    }
+    let mut elabel = |src: VId, dst: VId, undirected: bool| {
+        let (i, j) = (mapping[&src], mapping[&dst]);
+        let mut elabels = elabels_between(graph, i, j);
+        if undirected {
+            elabels.extend(elabels_between(graph, j, i));
+        }
+        *elabels.choose(&mut rng).unwrap()
+    };
+    let arcs = ast
+        .arcs()
+        .iter()
+        .map(|&(src, dst, _)| (src, dst, elabel(src, dst, false)))
+        .collect();
+    let edges = ast
+        .edges()
+        .iter()
+        .map(|&(src, dst, _)| (src, dst, elabel(src, dst, true)))
+        .collect();
+    Ok(Ast::new(
+        ast.vertices()
+            .iter()
+            .map(|&(vid, _)| (vid, graph.vlabel(mapping[&vid])))
+            .collect(),
+        arcs,
+        edges,
+        ast.constraint().cloned(),
+    )
+    .to_string())
+}
+
+/// Orders the pattern vertices so that each one has as many preceding neighbours as possible.
+fn embedding_order(ast: &Ast) -> Vec<VId> {
+    let mut vids: Vec<VId> = ast.vertices().iter().map(|&(vid, _)| vid).collect();
+    let mut neighbours: HashMap<VId, Vec<VId>> = HashMap::new();
+    for &(src, dst, _) in ast.arcs().iter().chain(ast.edges()) {
+        neighbours.entry(src).or_default().push(dst);
+        neighbours.entry(dst).or_default().push(src);
+        vids.extend(&[src, dst]);
+    }
+    vids.extend(ast.constraint().map(|expr| expr.vids()).unwrap_or_default());
+    let mut unordered = Vec::with_capacity(vids.len());
+    for vid in vids {
+        if !unordered.contains(&vid) {
+            unordered.push(vid);
+        }
+    }
+    let mut order = Vec::with_capacity(unordered.len());
+    while !unordered.is_empty() {
+        let neighbours = |vid| {
+            neighbours
+                .get(&vid)
+                .map(|vids| vids.as_slice())
+                .unwrap_or(&[])
+        };
+        let (k, _) = unordered
+            .iter()
+            .enumerate()
+            .max_by_key(|&(k, &vid)| {
+                (
+                    neighbours(vid)
+                        .iter()
+                        .filter(|&u| order.contains(u))
+                        .count(),
+                    neighbours(vid).len(),
+                    std::cmp::Reverse(k),
+                )
+            })
+            .unwrap();
+        order.push(unordered.remove(k));
+    }
+    order
+}
+
+/// Extends `mapping` to the vertices of `order` by backtracking, trying candidates in random
+/// order, and returns whether it succeeded.  Fails once more than `steps` candidates are tried.
+fn embed<R: Rng + ?Sized>(
+    ast: &Ast,
+    graph: &Graph,
+    order: &[VId],
+    mapping: &mut HashMap<VId, usize>,
+    steps: &mut usize,
+    rng: &mut R,
+) -> Result<bool, EmbeddingError> {
+    let u = match order.get(mapping.len()) {
+        Some(&u) => u,
+        None => {
+            return Ok(match ast.constraint() {
+                Some(expr) => {
+                    expr.eval(&|vid| graph.vid(mapping[&vid]) as i64, &|vid| {
+                        graph.vlabel(mapping[&vid]) as i64
+                    }) != 0
+                }
+                None => true,
+            })
+        }
+    };
+    let mut candidates = candidates(ast, graph, mapping, u);
+    candidates.shuffle(rng);
+    for i in candidates {
+        if *steps == 0 {
+            return Err(EmbeddingError::SearchLimitReached);
+        }
+        *steps -= 1;
+        if mapping.values().any(|&j| j == i) || !is_consistent(ast, graph, mapping, u, i) {
+            continue;
+        }
+        mapping.insert(u, i);
+        if embed(ast, graph, order, mapping, steps, rng)? {
+            return Ok(true);
+        }
+        mapping.remove(&u);
+    }
+    Ok(false)
+}
+
+/// Returns the neighbours of the data vertex of a mapped neighbour of `u`, or every data vertex.
+fn candidates(ast: &Ast, graph: &Graph, mapping: &HashMap<VId, usize>, u: VId) -> Vec<usize> {
+    let mut candidates: Vec<usize> = if let Some(j) = ast
+        .arcs()
+        .iter()
+        .find_map(|&(src, dst, _)| mapping.get(&dst).filter(|_| src == u))
+    {
+        graph.in_arcs(*j).iter().map(|&(i, _)| i).collect()
+    } else if let Some(j) = ast
+        .arcs()
+        .iter()
+        .find_map(|&(src, dst, _)| mapping.get(&src).filter(|_| dst == u))
+    {
+        graph.out_arcs(*j).iter().map(|&(i, _)| i).collect()
+    } else if let Some(j) = ast.edges().iter().find_map(|&(src, dst, _)| {
+        if src == u {
+            mapping.get(&dst)
+        } else if dst == u {
+            mapping.get(&src)
+        } else {
+            None
+        }
+    }) {
+        graph
+            .out_arcs(*j)
+            .iter()
+            .chain(graph.in_arcs(*j))
+            .map(|&(i, _)| i)
+            .collect()
+    } else {
+        (0..graph.num_vertices()).collect()
+    };
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Returns whether the arcs and edges between `u` and the mapped vertices exist in the data
+/// graph if `u` is mapped to `i`.
+fn is_consistent(
+    ast: &Ast,
+    graph: &Graph,
+    mapping: &HashMap<VId, usize>,
+    u: VId,
+    i: usize,
+) -> bool {
+    let index = |vid| {
+        if vid == u {
+            Some(i)
+        } else {
+            mapping.get(&vid).cloned()
+        }
+    };
+    let is_mapped =
+        |src, dst| (src == u || dst == u) && index(src).is_some() && index(dst).is_some();
+    ast.arcs()
+        .iter()
+        .filter(|&&(src, dst, _)| is_mapped(src, dst))
+        .all(|&(src, dst, _)| {
+            !elabels_between(graph, index(src).unwrap(), index(dst).unwrap()).is_empty()
+        })
+        && ast
+            .edges()
+            .iter()
+            .filter(|&&(src, dst, _)| is_mapped(src, dst))
+            .all(|&(src, dst, _)| {
+                let (i, j) = (index(src).unwrap(), index(dst).unwrap());
+                !elabels_between(graph, i, j).is_empty() || !elabels_between(graph, j, i).is_empty()
+            })
+}
+
+fn elabels_between(graph: &Graph, i: usize, j: usize) -> Vec<ELabel> {
+    graph
+        .out_arcs(i)
+        .iter()
+        .filter(|&&(k, _)| k == j)
+        .map(|&(_, elabel)| elabel)
+        .collect()
 }
 
 pub fn gisp_to_star(ast: &Ast, root: VId) -> String {
@@ -57,7 +338,117 @@ pub fn gisp_to_star(ast: &Ast, root: VId) -> String {
             .collect(),
         arcs,
         edges,
-        ast.constraint().map(|expr| expr.clone()),
+        ast.constraint()
+            .and_then(|expr| expr.retain(&|vid| vertex_set.contains(&vid))),
     )
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_graph::write_sqlite3, pattern_graph::parse};
+
+    #[test]
+    fn test_gisp_to_gisp_uniform() {
+        let ast = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0) (u4 0)) (arcs (u1 u2 0) (u2 u3 0)) \
+             (edges (u3 u4 0) (u4 u1 0)))",
+        )
+        .unwrap();
+        let labeling = Labeling::Uniform {
+            num_vlabels: 4,
+            num_elabels: 3,
+        };
+        // As relabelled by earlier versions.
+        assert_eq!(
+            gisp_to_gisp(&ast, &labeling).unwrap(),
+            "(match (vertices (u1 1) (u2 2) (u3 0) (u4 0)) (arcs (u1 u2 0) (u2 u3 1)) \
+             (edges (u3 u4 0) (u4 u1 2)))"
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_gisp_embedded() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 5), (2, 6), (3, 7), (4, 8)],
+            vec![(1, 2, 1), (2, 3, 2), (3, 1, 3), (3, 4, 9)],
+        )
+        .unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        let ast = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u2 u3 0) (u3 u1 0)) \
+             (where (= (label u1) 5)))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_gisp_embedded(&ast, &graph).unwrap(),
+            "(match (vertices (u1 5) (u2 6) (u3 7)) (arcs (u1 u2 1) (u2 u3 2) (u3 u1 3)) \
+             (where (= (label u1) 5)))"
+        );
+        let ast =
+            parse("(match (vertices (u1 0) (u2 0)) (edges (u1 u2 0)) (where (> u1 u2)))").unwrap();
+        let relabelled = parse(&gisp_to_gisp_embedded(&ast, &graph).unwrap()).unwrap();
+        assert!(relabelled.vertices()[0].1 > relabelled.vertices()[1].1);
+        let ast = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0) (u4 0)) \
+             (arcs (u1 u2 0) (u2 u3 0) (u3 u4 0) (u4 u1 0)))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_gisp_embedded(&ast, &graph),
+            Err(EmbeddingError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_embed_search_limit() {
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            (1..=4).map(|vid| (vid, 0)),
+            vec![(1, 2, 0), (2, 3, 0), (3, 4, 0)],
+        )
+        .unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        let ast =
+            parse("(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u2 u3 0) (u3 u1 0)))")
+                .unwrap();
+        let order = embedding_order(&ast);
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(SEED);
+        assert_eq!(
+            embed(&ast, &graph, &order, &mut HashMap::new(), &mut 2, &mut rng),
+            Err(EmbeddingError::SearchLimitReached)
+        );
+        assert_eq!(
+            embed(
+                &ast,
+                &graph,
+                &order,
+                &mut HashMap::new(),
+                &mut 100,
+                &mut rng
+            ),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_star() {
+        assert_eq!(
+            gisp_to_star(
+                &parse(
+                    "(match (vertices (u1 1) (u2 2) (u3 3) (u4 4)) \
+                     (arcs (u1 u2 0) (u2 u3 0) (u3 u4 0)) \
+                     (where (and (< u1 u3) (< u2 u4) (= (label u1) 1))))"
+                )
+                .unwrap(),
+                2
+            ),
+            "(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 0) (u2 u3 0)) \
+             (where (and (< u1 u3) (= (label u1) 1))))"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    data_graph::graph_format::write_graph,
+    pattern_graph::{Ast, UnsupportedExpr},
+    types::{ELabel, VId},
+};
+use derive_more::{Display, Error};
+use std::collections::HashMap;
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum UnsupportedGraph {
+    #[display(fmt = "graph has no directed arcs, found (u{} u{} {})", _0, _1, _2)]
+    Arc(
+        #[error(not(source))] VId,
+        #[error(not(source))] VId,
+        #[error(not(source))] ELabel,
+    ),
+    #[display(fmt = "{}", _0)]
+    Expr(UnsupportedExpr),
+}
+
+/// Translates the pattern to the graph format of subgraph matching benchmarks, see
+/// `data_graph::graph_format`.
+///
+/// The format only has undirected edges and no filter, so arcs are rejected and only constraints
+/// implied by the pattern itself are accepted.  Vertices are renumbered `0..n` in the order of
+/// the `vertices` section.
+pub fn gisp_to_graph(ast: &Ast) -> Result<String, UnsupportedGraph> {
+    if let Some(&(src, dst, elabel)) = ast.arcs().first() {
+        return Err(UnsupportedGraph::Arc(src, dst, elabel));
+    }
+    if let Some(expr) = ast.constraint() {
+        let vid_vlabels: HashMap<_, _> = ast.vertices().iter().cloned().collect();
+        expr.check_implied("graph", &vid_vlabels)
+            .map_err(UnsupportedGraph::Expr)?;
+    }
+    let mut buf = Vec::new();
+    write_graph(&mut buf, ast.vertices(), ast.edges()).unwrap();
+    Ok(String::from_utf8(buf).unwrap().trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::{graph_to_gisp, parse};
+
+    #[test]
+    fn test_gisp_to_graph() {
+        let ast = parse(
+            "(match (vertices (u3 1) (u5 2) (u7 1)) (edges (u3 u5 0) (u5 u7 4)) \
+             (where (= (label u5) 2)))",
+        )
+        .unwrap();
+        let graph = gisp_to_graph(&ast).unwrap();
+        assert_eq!(graph, "t 3 2\nv 0 1 1\nv 1 2 2\nv 2 1 1\ne 0 1 0\ne 1 2 4");
+        assert_eq!(
+            graph_to_gisp(&graph).unwrap().to_string(),
+            "(match (vertices (u0 1) (u1 2) (u2 1)) (edges (u0 u1 0) (u1 u2 4)))"
+        );
+        assert_eq!(
+            gisp_to_graph(&parse("(match (vertices (u1 0) (u2 0)) (arcs (u1 u2 3)))").unwrap()),
+            Err(UnsupportedGraph::Arc(1, 2, 3))
+        );
+    }
+}
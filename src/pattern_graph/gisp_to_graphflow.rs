@@ -1,27 +1,165 @@
-use crate::pattern_graph::Ast;
+use crate::{
+    pattern_graph::{Ast, Semantics, UnsupportedExpr},
+    types::{ELabel, VId, VLabel},
+};
+use derive_more::{Display, Error};
 use std::collections::HashMap;
 
-pub fn gisp_to_graphflow(ast: &Ast) -> String {
+/// Maximum number of undirected edges, whose `2^k` orientations are written one per line.
+pub const MAX_UNDIRECTED_EDGES: usize = 16;
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum UnsupportedGraphflow {
+    #[display(
+        fmt = "graphflow cannot match u{} and u{} to distinct vertices",
+        _0,
+        _1
+    )]
+    DistinctVertices(#[error(not(source))] VId, #[error(not(source))] VId),
+    #[display(
+        fmt = "graphflow cannot match (u{} u{}) and (u{} u{}) to distinct edges",
+        _0,
+        _1,
+        _2,
+        _3
+    )]
+    DistinctEdges(
+        #[error(not(source))] VId,
+        #[error(not(source))] VId,
+        #[error(not(source))] VId,
+        #[error(not(source))] VId,
+    ),
+    #[display(
+        fmt = "graphflow cannot match u{} and u{} to non-adjacent vertices",
+        _0,
+        _1
+    )]
+    NonAdjacent(#[error(not(source))] VId, #[error(not(source))] VId),
+    #[display(fmt = "graphflow cannot match isolated vertex u{}", _0)]
+    IsolatedVertex(#[error(not(source))] VId),
+    #[display(
+        fmt = "graphflow cannot lower {} undirected edges, at most {} are supported",
+        _0,
+        MAX_UNDIRECTED_EDGES
+    )]
+    TooManyEdges(#[error(not(source))] usize),
+    #[display(fmt = "{}", _0)]
+    Expr(UnsupportedExpr),
+}
+
+/// GraphFlow has no filter syntax, so only constraints implied by the pattern itself are
+/// accepted, i.e. conjunctions of `(= (label uN) L)` where `L` is the label of `uN`.
+///
+/// GraphFlow only matches directed edges, so a pattern with `k` undirected edges is lowered
+/// into the union of its `2^k` orientations, written one query per line.  Summing the counts of
+/// all the queries gives the number of embeddings where each undirected edge is mapped to a data
+/// arc in either direction, counted once per data arc as by `count_arc_matches`.  Patterns with
+/// more than `MAX_UNDIRECTED_EDGES` undirected edges are rejected.
+///
+/// Queries are made of edges only, so patterns with isolated vertices are rejected.
+///
+/// GraphFlow computes homomorphisms and cannot be told otherwise, so other `semantics` are
+/// only accepted when the labels of the pattern already rule out the matches they exclude.
+pub fn gisp_to_graphflow(
+    ast: &Ast,
+    semantics: Option<Semantics>,
+) -> Result<String, UnsupportedGraphflow> {
     let vid_vlabels: HashMap<_, _> = ast
         .vertices()
         .iter()
         .map(|&(vid, vlabel)| (vid, vlabel))
         .collect();
-    let results: Vec<String> = ast
+    if let Some(expr) = ast.constraint() {
+        expr.check_implied("graphflow", &vid_vlabels)
+            .map_err(UnsupportedGraphflow::Expr)?;
+    }
+    if let Some(&(vid, _)) = ast.vertices().iter().find(|&&(vid, _)| {
+        !ast.arcs()
+            .iter()
+            .chain(ast.edges())
+            .any(|&(src, dst, _)| src == vid || dst == vid)
+    }) {
+        return Err(UnsupportedGraphflow::IsolatedVertex(vid));
+    }
+    if ast.edges().len() > MAX_UNDIRECTED_EDGES {
+        return Err(UnsupportedGraphflow::TooManyEdges(ast.edges().len()));
+    }
+    let semantics = semantics.unwrap_or(Semantics::Homomorphism);
+    if let Some(&(lhs, rhs)) = semantics.distinct_pairs(ast).first() {
+        return Err(UnsupportedGraphflow::DistinctVertices(lhs, rhs));
+    }
+    if let Some(&(lhs, rhs)) = semantics.non_adjacent_pairs(ast).first() {
+        return Err(UnsupportedGraphflow::NonAdjacent(lhs, rhs));
+    }
+    if semantics == Semantics::EdgeIsomorphism {
+        check_distinct_edges(ast, &vid_vlabels)?;
+    }
+    let arc_to_graphflow = |&(src, dst, elabel): &(VId, VId, ELabel)| {
+        format!(
+            "(u{}:{})-[{}]->(u{}:{})",
+            src,
+            vid_vlabels.get(&src).unwrap(),
+            elabel + 1,
+            dst,
+            vid_vlabels.get(&dst).unwrap()
+        )
+    };
+    let arcs: Vec<String> = ast.arcs().iter().map(arc_to_graphflow).collect();
+    let queries: Vec<String> = (0..1u32 << ast.edges().len())
+        .map(|orientation| {
+            arcs.iter()
+                .cloned()
+                .chain(
+                    ast.edges()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &(src, dst, elabel))| {
+                            if orientation >> i & 1 == 0 {
+                                arc_to_graphflow(&(src, dst, elabel))
+                            } else {
+                                arc_to_graphflow(&(dst, src, elabel))
+                            }
+                        }),
+                )
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect();
+    Ok(queries.join("\n"))
+}
+
+/// Rejects pairs of arcs or edges a homomorphism may map to the same data arc, i.e. with the
+/// same label between vertices of the same labels.
+fn check_distinct_edges(
+    ast: &Ast,
+    vid_vlabels: &HashMap<VId, VLabel>,
+) -> Result<(), UnsupportedGraphflow> {
+    let links: Vec<(VId, VId, ELabel, bool)> = ast
         .arcs()
         .iter()
-        .map(|&(src, dst, elabel)| {
-            format!(
-                "(u{}:{})-[{}]->(u{}:{})",
-                src,
-                vid_vlabels.get(&src).unwrap(),
-                elabel + 1,
-                dst,
-                vid_vlabels.get(&dst).unwrap()
-            )
-        })
+        .map(|&(src, dst, elabel)| (src, dst, elabel, false))
+        .chain(
+            ast.edges()
+                .iter()
+                .map(|&(src, dst, elabel)| (src, dst, elabel, true)),
+        )
         .collect();
-    results.join(",")
+    let same_vlabels = |u, v| vid_vlabels.get(&u) == vid_vlabels.get(&v);
+    for (i, &(src, dst, elabel, undirected)) in links.iter().enumerate() {
+        for &(other_src, other_dst, other_elabel, other_undirected) in &links[i + 1..] {
+            let collapses = elabel == other_elabel
+                && ((same_vlabels(src, other_src) && same_vlabels(dst, other_dst))
+                    || ((undirected || other_undirected)
+                        && same_vlabels(src, other_dst)
+                        && same_vlabels(dst, other_src)));
+            if collapses {
+                return Err(UnsupportedGraphflow::DistinctEdges(
+                    src, dst, other_src, other_dst,
+                ));
+            }
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -34,9 +172,106 @@ mod tests {
         assert_eq!(
             gisp_to_graphflow(
                 &parse("(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 12) (u1 u3 13)))")
-                    .unwrap()
+                    .unwrap(),
+                None
             ),
-            "(u1:1)-[13]->(u2:2),(u1:1)-[14]->(u3:3)"
+            Ok("(u1:1)-[13]->(u2:2),(u1:1)-[14]->(u3:3)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_graphflow_edges() {
+        assert_eq!(
+            gisp_to_graphflow(
+                &parse(
+                    "(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 12)) \
+                     (edges (u2 u3 23) (u3 u1 31)))"
+                )
+                .unwrap(),
+                None
+            ),
+            Ok([
+                "(u1:1)-[13]->(u2:2),(u2:2)-[24]->(u3:3),(u3:3)-[32]->(u1:1)",
+                "(u1:1)-[13]->(u2:2),(u3:3)-[24]->(u2:2),(u3:3)-[32]->(u1:1)",
+                "(u1:1)-[13]->(u2:2),(u2:2)-[24]->(u3:3),(u1:1)-[32]->(u3:3)",
+                "(u1:1)-[13]->(u2:2),(u3:3)-[24]->(u2:2),(u1:1)-[32]->(u3:3)",
+            ]
+            .join("\n"))
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_graphflow_isolated() {
+        assert_eq!(
+            gisp_to_graphflow(&parse("(match (vertices (u1 1)))").unwrap(), None),
+            Err(UnsupportedGraphflow::IsolatedVertex(1))
+        );
+        assert_eq!(
+            gisp_to_graphflow(
+                &parse("(match (vertices (u1 1) (u2 2) (u3 3)) (edges (u1 u3 0)))").unwrap(),
+                None
+            ),
+            Err(UnsupportedGraphflow::IsolatedVertex(2))
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_graphflow_too_many_edges() {
+        let edges: Vec<String> = (1..=MAX_UNDIRECTED_EDGES + 1)
+            .map(|vid| format!("(u{} u{} 0)", vid, vid + 1))
+            .collect();
+        let vertices: Vec<String> = (1..=MAX_UNDIRECTED_EDGES + 2)
+            .map(|vid| format!("(u{} 0)", vid))
+            .collect();
+        let gisp = format!(
+            "(match (vertices {}) (edges {}))",
+            vertices.join(" "),
+            edges.join(" ")
+        );
+        assert_eq!(
+            gisp_to_graphflow(&parse(&gisp).unwrap(), None),
+            Err(UnsupportedGraphflow::TooManyEdges(MAX_UNDIRECTED_EDGES + 1))
+        );
+    }
+
+    #[test]
+    fn test_gisp_to_graphflow_where() {
+        assert_eq!(
+            gisp_to_graphflow(
+                &parse(
+                    "(match (vertices (u1 1) (u2 2)) (arcs (u1 u2 12)) (where (= (label u2) 2)))"
+                )
+                .unwrap(),
+                None
+            ),
+            Ok("(u1:1)-[13]->(u2:2)".to_string())
+        );
+        assert!(gisp_to_graphflow(
+            &parse("(match (vertices (u1 1) (u2 2)) (arcs (u1 u2 12)) (where (< u1 u2)))").unwrap(),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_gisp_to_graphflow_semantics() {
+        let path =
+            parse("(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 0) (u2 u3 0)))").unwrap();
+        assert!(gisp_to_graphflow(&path, Some(Semantics::VertexIsomorphism)).is_ok());
+        assert!(gisp_to_graphflow(&path, Some(Semantics::EdgeIsomorphism)).is_ok());
+        assert_eq!(
+            gisp_to_graphflow(&path, Some(Semantics::Induced)),
+            Err(UnsupportedGraphflow::NonAdjacent(1, 3))
+        );
+        let wedge =
+            parse("(match (vertices (u1 1) (u2 2) (u3 2)) (arcs (u1 u2 0) (u1 u3 0)))").unwrap();
+        assert_eq!(
+            gisp_to_graphflow(&wedge, Some(Semantics::VertexIsomorphism)),
+            Err(UnsupportedGraphflow::DistinctVertices(2, 3))
+        );
+        assert_eq!(
+            gisp_to_graphflow(&wedge, Some(Semantics::EdgeIsomorphism)),
+            Err(UnsupportedGraphflow::DistinctEdges(1, 2, 1, 3))
         );
     }
 }
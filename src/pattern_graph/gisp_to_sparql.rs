@@ -0,0 +1,154 @@
+use crate::{
+    data_graph::{ELABEL_IRI, VERTEX_IRI, VLABEL_IRI},
+    pattern_graph::{Ast, Expr, Semantics},
+    types::{ELabel, VId, VLabel},
+};
+use std::collections::HashMap;
+
+/// Translates the pattern to a SPARQL query over the triples of `data_graph::sqlite3_to_ntriples`,
+/// which returns the vertices matched by `ast.vertices()`.
+///
+/// Undirected edges are alternative paths of both directions, and matches are distinct rows.
+/// Basic graph patterns compute homomorphisms, so filters are added for `semantics`.  Vertex ids
+/// are parsed back from the IRIs, while labels are those of the pattern.
+pub fn gisp_to_sparql(ast: &Ast, semantics: Option<Semantics>) -> String {
+    let semantics = semantics.unwrap_or(Semantics::Homomorphism);
+    let vid_vlabels: HashMap<VId, VLabel> = ast.vertices().iter().cloned().collect();
+    let mut patterns: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, vlabel)| format!("?u{} a <{}{}> .", vid, VLABEL_IRI, vlabel))
+        .collect();
+    for &(src, dst, elabel) in ast.arcs() {
+        patterns.push(format!("?u{} <{}{}> ?u{} .", src, ELABEL_IRI, elabel, dst));
+    }
+    // Ends of the data arcs matched by the arcs and edges, to keep them distinct.
+    let mut ends: Vec<(String, String, ELabel)> = ast
+        .arcs()
+        .iter()
+        .map(|&(src, dst, elabel)| (format!("?u{}", src), format!("?u{}", dst), elabel))
+        .collect();
+    for (i, &(src, dst, elabel)) in ast.edges().iter().enumerate() {
+        let predicate = format!("<{}{}>", ELABEL_IRI, elabel);
+        if semantics == Semantics::EdgeIsomorphism {
+            let (e_src, e_dst) = (format!("?e{}_src", i + 1), format!("?e{}_dst", i + 1));
+            patterns.push(format!(
+                "{{ ?u{0} {2} ?u{1} . BIND (?u{0} AS {3}) BIND (?u{1} AS {4}) }} \
+                 UNION {{ ?u{1} {2} ?u{0} . BIND (?u{1} AS {3}) BIND (?u{0} AS {4}) }}",
+                src, dst, predicate, e_src, e_dst
+            ));
+            ends.push((e_src, e_dst, elabel));
+        } else {
+            patterns.push(format!(
+                "?u{} {}|^{} ?u{} .",
+                src, predicate, predicate, dst
+            ));
+        }
+    }
+    if let Some(expr) = ast.constraint() {
+        patterns.push(format!("FILTER ({})", expr_to_sparql(expr, &vid_vlabels)));
+    }
+    if semantics == Semantics::EdgeIsomorphism {
+        for (i, (src, dst, elabel)) in ends.iter().enumerate() {
+            for (other_src, other_dst, other_elabel) in &ends[i + 1..] {
+                if elabel == other_elabel {
+                    patterns.push(format!(
+                        "FILTER ({} != {} || {} != {})",
+                        src, other_src, dst, other_dst
+                    ));
+                }
+            }
+        }
+    }
+    for (lhs, rhs) in semantics.distinct_pairs(ast) {
+        patterns.push(format!("FILTER (?u{} != ?u{})", lhs, rhs));
+    }
+    for (lhs, rhs) in semantics.non_adjacent_pairs(ast) {
+        patterns.push(format!(
+            "FILTER NOT EXISTS {{ {{ ?u{0} ?p ?u{1} }} UNION {{ ?u{1} ?p ?u{0} }} }}",
+            lhs, rhs
+        ));
+    }
+    let vars: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, _)| format!("?u{}", vid))
+        .collect();
+    format!(
+        "SELECT DISTINCT {} WHERE {{\n{}}}",
+        vars.join(" "),
+        patterns
+            .iter()
+            .map(|pattern| format!("  {}\n", pattern))
+            .collect::<String>()
+    )
+}
+
+fn expr_to_sparql(expr: &Expr, vid_vlabels: &HashMap<VId, VLabel>) -> String {
+    match expr {
+        Expr::Int(n) => n.to_string(),
+        Expr::VId(vid) => format!(
+            "<http://www.w3.org/2001/XMLSchema#integer>(STRAFTER(STR(?u{}), \"{}\"))",
+            vid, VERTEX_IRI
+        ),
+        Expr::VLabel(vid) => vid_vlabels[vid].to_string(),
+        Expr::Not(expr) => format!("!({})", expr_to_sparql(expr, vid_vlabels)),
+        Expr::And(exprs) => format!("({})", exprs_to_sparql(exprs, " && ", vid_vlabels)),
+        Expr::Or(exprs) => format!("({})", exprs_to_sparql(exprs, " || ", vid_vlabels)),
+        Expr::Cmp(op, lhs, rhs) => format!(
+            "{} {} {}",
+            expr_to_sparql(lhs, vid_vlabels),
+            op,
+            expr_to_sparql(rhs, vid_vlabels)
+        ),
+    }
+}
+
+fn exprs_to_sparql(exprs: &[Expr], sep: &str, vid_vlabels: &HashMap<VId, VLabel>) -> String {
+    exprs
+        .iter()
+        .map(|expr| expr_to_sparql(expr, vid_vlabels))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::parse;
+
+    #[test]
+    fn test_gisp_to_sparql() {
+        let ast = parse(
+            "(match (vertices (u1 1) (u2 1) (u3 2)) (arcs (u1 u2 0)) (edges (u2 u3 0)) \
+             (where (or (< u1 u3) (!= (label u1) 1))))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_sparql(&ast, None),
+            "SELECT DISTINCT ?u1 ?u2 ?u3 WHERE {
+  ?u1 a <urn:opgm:vlabel:1> .
+  ?u2 a <urn:opgm:vlabel:1> .
+  ?u3 a <urn:opgm:vlabel:2> .
+  ?u1 <urn:opgm:elabel:0> ?u2 .
+  ?u2 <urn:opgm:elabel:0>|^<urn:opgm:elabel:0> ?u3 .
+  FILTER ((<http://www.w3.org/2001/XMLSchema#integer>(STRAFTER(STR(?u1), \"urn:opgm:v:\")) \
+< <http://www.w3.org/2001/XMLSchema#integer>(STRAFTER(STR(?u3), \"urn:opgm:v:\")) || 1 != 1))
+}"
+        );
+        let ast =
+            parse("(match (vertices (u1 1) (u2 1)) (arcs (u1 u2 0)) (edges (u2 u1 0)))").unwrap();
+        assert_eq!(
+            gisp_to_sparql(&ast, Some(Semantics::EdgeIsomorphism)),
+            "SELECT DISTINCT ?u1 ?u2 WHERE {
+  ?u1 a <urn:opgm:vlabel:1> .
+  ?u2 a <urn:opgm:vlabel:1> .
+  ?u1 <urn:opgm:elabel:0> ?u2 .
+  { ?u2 <urn:opgm:elabel:0> ?u1 . BIND (?u2 AS ?e1_src) BIND (?u1 AS ?e1_dst) } \
+UNION { ?u1 <urn:opgm:elabel:0> ?u2 . BIND (?u1 AS ?e1_src) BIND (?u2 AS ?e1_dst) }
+  FILTER (?u1 != ?e1_src || ?u2 != ?e1_dst)
+}"
+        );
+        assert!(gisp_to_sparql(&ast, Some(Semantics::Induced)).contains("FILTER (?u1 != ?u2)"));
+    }
+}
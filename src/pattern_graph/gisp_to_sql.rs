@@ -0,0 +1,174 @@
+use crate::{
+    pattern_graph::{Ast, Expr, Semantics},
+    types::{ELabel, VId},
+};
+
+/// Translates the pattern to a SQL query over the `vertices` and `edges` tables, which returns
+/// the data vertex ids matched by `ast.vertices()`.
+///
+/// Each pattern vertex is a `uN` alias of `vertices` and each arc or edge an `eN` alias of
+/// `edges`.  Matches are distinct rows, so parallel data arcs do not count twice.  Joins compute
+/// homomorphisms, the default, so injectivity filters are added for other `semantics`.
+pub fn gisp_to_sql(ast: &Ast, semantics: Option<Semantics>) -> String {
+    let semantics = semantics.unwrap_or(Semantics::Homomorphism);
+    let mut tables: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, _)| format!("vertices u{}", vid))
+        .collect();
+    let mut predicates: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, vlabel)| format!("u{}.vlabel = {}", vid, vlabel))
+        .collect();
+    let links: Vec<(VId, VId, ELabel, bool)> = ast
+        .arcs()
+        .iter()
+        .map(|&(src, dst, elabel)| (src, dst, elabel, false))
+        .chain(
+            ast.edges()
+                .iter()
+                .map(|&(src, dst, elabel)| (src, dst, elabel, true)),
+        )
+        .collect();
+    for (i, &(src, dst, elabel, undirected)) in links.iter().enumerate() {
+        let e = i + 1;
+        tables.push(format!("edges e{}", e));
+        predicates.push(match undirected {
+            false => format!("e{0}.src = u{1}.vid AND e{0}.dst = u{2}.vid", e, src, dst),
+            true => format!(
+                "((e{0}.src = u{1}.vid AND e{0}.dst = u{2}.vid) \
+                 OR (e{0}.src = u{2}.vid AND e{0}.dst = u{1}.vid))",
+                e, src, dst
+            ),
+        });
+        predicates.push(format!("e{}.elabel = {}", e, elabel));
+    }
+    if let Some(expr) = ast.constraint() {
+        predicates.push(expr_to_sql(expr));
+    }
+    if semantics == Semantics::EdgeIsomorphism {
+        for (i, &(_, _, elabel, _)) in links.iter().enumerate() {
+            for (j, &(_, _, other_elabel, _)) in links.iter().enumerate().skip(i + 1) {
+                if elabel == other_elabel {
+                    predicates.push(format!("e{}.rowid <> e{}.rowid", i + 1, j + 1));
+                }
+            }
+        }
+    }
+    for (lhs, rhs) in semantics.distinct_pairs(ast) {
+        predicates.push(format!("u{}.vid <> u{}.vid", lhs, rhs));
+    }
+    for (lhs, rhs) in semantics.non_adjacent_pairs(ast) {
+        predicates.push(format!(
+            "NOT EXISTS (SELECT * FROM edges \
+             WHERE (src = u{0}.vid AND dst = u{1}.vid) OR (src = u{1}.vid AND dst = u{0}.vid))",
+            lhs, rhs
+        ));
+    }
+    let columns: Vec<String> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, _)| format!("u{}.vid", vid))
+        .collect();
+    let mut query = format!(
+        "SELECT DISTINCT {} FROM {}",
+        columns.join(", "),
+        tables.join(", ")
+    );
+    if !predicates.is_empty() {
+        query.push_str(&format!(" WHERE {}", predicates.join(" AND ")));
+    }
+    query
+}
+
+/// Runs the query of `gisp_to_sql` against the data graph, calling `f` with the data vertex
+/// ids of every match, and returns the number of matches.
+pub fn find_sql_matches(
+    conn: &sqlite::Connection,
+    ast: &Ast,
+    semantics: Option<Semantics>,
+    f: &mut dyn FnMut(&[VId]),
+) -> sqlite::Result<usize> {
+    let mut count = 0;
+    let mut vids = Vec::with_capacity(ast.vertices().len());
+    let mut stat = conn.prepare(gisp_to_sql(ast, semantics))?;
+    while let sqlite::State::Row = stat.next()? {
+        vids.clear();
+        for i in 0..ast.vertices().len() {
+            vids.push(stat.read::<i64>(i)? as VId);
+        }
+        f(&vids);
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn expr_to_sql(expr: &Expr) -> String {
+    match expr {
+        Expr::Int(n) => n.to_string(),
+        Expr::VId(vid) => format!("u{}.vid", vid),
+        Expr::VLabel(vid) => format!("u{}.vlabel", vid),
+        Expr::Not(expr) => format!("NOT ({})", expr_to_sql(expr)),
+        Expr::And(exprs) => format!("({})", exprs_to_sql(exprs, " AND ")),
+        Expr::Or(exprs) => format!("({})", exprs_to_sql(exprs, " OR ")),
+        Expr::Cmp(op, lhs, rhs) => format!("{} {} {}", expr_to_sql(lhs), op, expr_to_sql(rhs)),
+    }
+}
+
+fn exprs_to_sql(exprs: &[Expr], sep: &str) -> String {
+    exprs.iter().map(expr_to_sql).collect::<Vec<_>>().join(sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_graph::{write_sqlite3, Graph},
+        pattern_graph::{count_matches, parse},
+    };
+
+    #[test]
+    fn test_gisp_to_sql() {
+        let ast = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0)) (edges (u2 u3 0)) \
+             (where (or (< u1 u3) (!= (label u1) 1))))",
+        )
+        .unwrap();
+        assert_eq!(
+            gisp_to_sql(&ast, None),
+            gisp_to_sql(&ast, Some(Semantics::Homomorphism))
+        );
+        assert_eq!(
+            gisp_to_sql(&ast, Some(Semantics::Homomorphism)),
+            "SELECT DISTINCT u1.vid, u2.vid, u3.vid \
+             FROM vertices u1, vertices u2, vertices u3, edges e1, edges e2 \
+             WHERE u1.vlabel = 0 AND u2.vlabel = 0 AND u3.vlabel = 0 \
+             AND e1.src = u1.vid AND e1.dst = u2.vid AND e1.elabel = 0 \
+             AND ((e2.src = u2.vid AND e2.dst = u3.vid) OR (e2.src = u3.vid AND e2.dst = u2.vid)) \
+             AND e2.elabel = 0 AND (u1.vid < u3.vid OR u1.vlabel != 1)"
+        );
+
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 0), (2, 0), (3, 0), (4, 0)],
+            vec![(1, 2, 0), (2, 3, 0), (3, 1, 0), (1, 3, 0), (3, 4, 0)],
+        )
+        .unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        for semantics in [
+            Semantics::Homomorphism,
+            Semantics::EdgeIsomorphism,
+            Semantics::VertexIsomorphism,
+            Semantics::Induced,
+        ]
+        .iter()
+        {
+            assert_eq!(
+                find_sql_matches(&conn, &ast, Some(*semantics), &mut |_| ()).unwrap(),
+                count_matches(&ast, &graph, *semantics)
+            );
+        }
+    }
+}
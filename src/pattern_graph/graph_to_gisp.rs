@@ -0,0 +1,35 @@
+use crate::{
+    data_graph::graph_format::{read_graph, GraphFormatError},
+    pattern_graph::Ast,
+};
+
+/// Reads a query graph of subgraph matching benchmarks, see `data_graph::graph_format`.
+///
+/// Vertex `N` of the file becomes `uN`, and edges become undirected edges.  Negative vertex ids
+/// are rejected, as `u-N` is not a gisp vertex.
+pub fn graph_to_gisp(graph: &str) -> Result<Ast, GraphFormatError> {
+    let (vertices, edges) = read_graph(graph.as_bytes())?;
+    if let Some(&(vid, _)) = vertices.iter().find(|&&(vid, _)| vid < 0) {
+        return Err(GraphFormatError::NegativeVertex(vid));
+    }
+    Ok(Ast::new(vertices, vec![], edges, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_to_gisp() {
+        assert_eq!(
+            graph_to_gisp("t 2 1\nv 0 1 1\nv 3 2 1\ne 0 3 4\n")
+                .unwrap()
+                .to_string(),
+            "(match (vertices (u0 1) (u3 2)) (edges (u0 u3 4)))"
+        );
+        assert!(matches!(
+            graph_to_gisp("t 2 1\nv 0 1 1\nv -1 2 1\ne 0 -1\n"),
+            Err(GraphFormatError::NegativeVertex(-1))
+        ));
+    }
+}
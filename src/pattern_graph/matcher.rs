@@ -0,0 +1,459 @@
+//! Reference subgraph matcher
+//!
+//! Enumerates the matches of a pattern in an in-memory data graph by backtracking, to check the
+//! counts of the engines the translators target.  It favours simplicity over speed.
+
+use crate::{
+    data_graph::Graph,
+    pattern_graph::Ast,
+    types::{ELabel, VId, VLabel},
+};
+use std::collections::HashMap;
+
+/// Which mappings of the pattern into the data graph count as matches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Semantics {
+    /// Any mapping which preserves labels, arcs and edges.
+    Homomorphism,
+    /// Distinct arcs and edges of the pattern are mapped to distinct arcs of the data graph.
+    EdgeIsomorphism,
+    /// Distinct vertices of the pattern are mapped to distinct vertices of the data graph.
+    VertexIsomorphism,
+    /// Vertex isomorphism where vertices which are not adjacent in the pattern are mapped to
+    /// vertices which are not adjacent in the data graph.
+    Induced,
+}
+
+impl Semantics {
+    /// Returns the pairs of pattern vertices a homomorphism must map to distinct data vertices
+    /// to match with these semantics, skipping pairs of different labels.
+    pub(crate) fn distinct_pairs(self, ast: &Ast) -> Vec<(VId, VId)> {
+        match self {
+            Semantics::Homomorphism | Semantics::EdgeIsomorphism => vec![],
+            Semantics::VertexIsomorphism | Semantics::Induced => {
+                let vertices = ast.vertices();
+                (0..vertices.len())
+                    .flat_map(|i| (i + 1..vertices.len()).map(move |j| (i, j)))
+                    .filter(|&(i, j)| vertices[i].1 == vertices[j].1)
+                    .map(|(i, j)| (vertices[i].0, vertices[j].0))
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the pairs of pattern vertices a homomorphism must map to non-adjacent data
+    /// vertices to match with these semantics.
+    pub(crate) fn non_adjacent_pairs(self, ast: &Ast) -> Vec<(VId, VId)> {
+        if self != Semantics::Induced {
+            return vec![];
+        }
+        let vertices = ast.vertices();
+        let is_adjacent = |u, v| {
+            ast.arcs()
+                .iter()
+                .chain(ast.edges())
+                .any(|&(src, dst, _)| (src, dst) == (u, v) || (src, dst) == (v, u))
+        };
+        (0..vertices.len())
+            .flat_map(|i| (i + 1..vertices.len()).map(move |j| (vertices[i].0, vertices[j].0)))
+            .filter(|&(u, v)| !is_adjacent(u, v))
+            .collect()
+    }
+}
+
+/// Calls `f` with the data vertex ids matched by `ast.vertices()` for every match satisfying
+/// the constraint, and returns the number of matches.
+///
+/// Undirected edges match arcs in either direction.
+pub fn find_matches(
+    ast: &Ast,
+    graph: &Graph,
+    semantics: Semantics,
+    f: &mut dyn FnMut(&[VId]),
+) -> usize {
+    let mut count = 0;
+    Search::new(ast, graph, semantics).run(&mut |images, _| {
+        count += 1;
+        f(&images.iter().map(|&i| graph.vid(i)).collect::<Vec<_>>())
+    });
+    count
+}
+
+/// Counts the distinct mappings of the pattern vertices to data vertices, as `find_sql_matches`
+/// does, so parallel and antiparallel data arcs with the same label count once.
+///
+/// Engines returning one result per data arc, such as Cypher and the GraphFlow orientations of
+/// undirected edges, count `count_arc_matches` instead.
+pub fn count_matches(ast: &Ast, graph: &Graph, semantics: Semantics) -> usize {
+    find_matches(ast, graph, semantics, &mut |_| ())
+}
+
+/// Counts the matches together with the data arcs of every pattern arc and edge, so each of
+/// parallel data arcs, and each direction of antiparallel ones matching an undirected edge,
+/// counts once.
+///
+/// Under edge isomorphisms, distinct pattern arcs and edges are mapped to distinct data arcs.
+pub fn count_arc_matches(ast: &Ast, graph: &Graph, semantics: Semantics) -> usize {
+    let mut count = 0;
+    Search::new(ast, graph, semantics).run(&mut |_, num_arc_mappings| count += num_arc_mappings);
+    count
+}
+
+/// Direction of a pattern arc or edge seen from the vertex being matched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Out,
+    In,
+    Either,
+}
+
+struct Search<'a> {
+    ast: &'a Ast,
+    graph: &'a Graph,
+    semantics: Semantics,
+    /// Pattern vertices, as indices of `ast.vertices()`, in matching order.
+    order: Vec<usize>,
+    /// Arcs and edges from each pattern vertex to itself or to those preceding it in `order`.
+    checks: Vec<Vec<(usize, Direction, ELabel)>>,
+    /// Whether two pattern vertices are joined by an arc or an edge.
+    adjacent: Vec<Vec<bool>>,
+    /// Data vertices of each label.
+    candidates: HashMap<VLabel, Vec<usize>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(ast: &'a Ast, graph: &'a Graph, semantics: Semantics) -> Self {
+        let n = ast.vertices().len();
+        let indices: HashMap<VId, usize> = ast
+            .vertices()
+            .iter()
+            .enumerate()
+            .map(|(i, &(vid, _))| (vid, i))
+            .collect();
+        let links: Vec<(usize, usize, ELabel, bool)> = ast
+            .arcs()
+            .iter()
+            .map(|&(src, dst, elabel)| (src, dst, elabel, false))
+            .chain(
+                ast.edges()
+                    .iter()
+                    .map(|&(src, dst, elabel)| (src, dst, elabel, true)),
+            )
+            .map(|(src, dst, elabel, undirected)| {
+                (indices[&src], indices[&dst], elabel, undirected)
+            })
+            .collect();
+        let mut adjacent = vec![vec![false; n]; n];
+        for &(src, dst, _, _) in &links {
+            adjacent[src][dst] = true;
+            adjacent[dst][src] = true;
+        }
+        let mut order: Vec<usize> = Vec::with_capacity(n);
+        let mut position = vec![None; n];
+        while order.len() < n {
+            let i = (0..n)
+                .filter(|&i| position[i].is_none())
+                .max_by_key(|&i| {
+                    (
+                        order.iter().filter(|&&j| adjacent[i][j]).count(),
+                        adjacent[i]
+                            .iter()
+                            .filter(|&&is_adjacent| is_adjacent)
+                            .count(),
+                        std::cmp::Reverse(i),
+                    )
+                })
+                .unwrap();
+            position[i] = Some(order.len());
+            order.push(i);
+        }
+        let mut checks = vec![Vec::new(); n];
+        for &(src, dst, elabel, undirected) in &links {
+            let (src_direction, dst_direction) = match undirected {
+                true => (Direction::Either, Direction::Either),
+                false => (Direction::Out, Direction::In),
+            };
+            if position[src] >= position[dst] {
+                checks[src].push((dst, src_direction, elabel));
+            } else {
+                checks[dst].push((src, dst_direction, elabel));
+            }
+        }
+        let mut candidates: HashMap<VLabel, Vec<usize>> = HashMap::new();
+        for i in 0..graph.num_vertices() {
+            candidates.entry(graph.vlabel(i)).or_default().push(i);
+        }
+        Self {
+            ast,
+            graph,
+            semantics,
+            order,
+            checks,
+            adjacent,
+            candidates,
+        }
+    }
+
+    /// Calls `f` with the images of every match and its number of mappings of the pattern arcs
+    /// and edges to data arcs.
+    fn run(&self, f: &mut dyn FnMut(&[usize], usize)) {
+        let n = self.ast.vertices().len();
+        self.extend(0, &mut vec![None; n], f);
+    }
+
+    /// Maps the pattern vertex at position `k` of the order, given `images` of the preceding ones.
+    fn extend(
+        &self,
+        k: usize,
+        images: &mut Vec<Option<usize>>,
+        f: &mut dyn FnMut(&[usize], usize),
+    ) {
+        let u = match self.order.get(k) {
+            Some(&u) => u,
+            None => {
+                let images: Vec<usize> = images.iter().map(|image| image.unwrap()).collect();
+                if !self.satisfies_constraint(&images) {
+                    return;
+                }
+                let num_arc_mappings = self.count_arc_mappings(&images);
+                if num_arc_mappings > 0 {
+                    f(&images, num_arc_mappings);
+                }
+                return;
+            }
+        };
+        for x in self.candidates(u, images) {
+            if !self.is_consistent(u, x, images) {
+                continue;
+            }
+            images[u] = Some(x);
+            self.extend(k + 1, images, f);
+            images[u] = None;
+        }
+    }
+
+    /// Returns the smallest of the sets of data vertices adjacent to the images of the matched
+    /// neighbours of `u`, or the data vertices with the label of `u`.
+    fn candidates(&self, u: usize, images: &[Option<usize>]) -> Vec<usize> {
+        self.checks[u]
+            .iter()
+            .filter(|&&(v, _, _)| v != u)
+            .map(|&(v, direction, elabel)| {
+                let j = images[v].unwrap();
+                let mut neighbours: Vec<usize> = Vec::new();
+                if direction != Direction::Out {
+                    neighbours.extend(self.neighbours(self.graph.out_arcs(j), elabel));
+                }
+                if direction != Direction::In {
+                    neighbours.extend(self.neighbours(self.graph.in_arcs(j), elabel));
+                }
+                neighbours.sort_unstable();
+                neighbours.dedup();
+                neighbours
+            })
+            .min_by_key(|neighbours| neighbours.len())
+            .unwrap_or_else(|| {
+                self.candidates
+                    .get(&self.ast.vertices()[u].1)
+                    .cloned()
+                    .unwrap_or_default()
+            })
+    }
+
+    fn neighbours<'b>(
+        &self,
+        arcs: &'b [(usize, ELabel)],
+        elabel: ELabel,
+    ) -> impl Iterator<Item = usize> + 'b {
+        arcs.iter()
+            .filter(move |&&(_, other)| other == elabel)
+            .map(|&(i, _)| i)
+    }
+
+    /// Checks the label of `x`, the arcs and edges between `u` and the matched vertices, and
+    /// the injectivity required by the semantics.
+    fn is_consistent(&self, u: usize, x: usize, images: &[Option<usize>]) -> bool {
+        if self.graph.vlabel(x) != self.ast.vertices()[u].1 {
+            return false;
+        }
+        let image = |v: usize| if v == u { x } else { images[v].unwrap() };
+        let has_arc =
+            |i: usize, j: usize, elabel| self.graph.out_arcs(i).binary_search(&(j, elabel)).is_ok();
+        let preserves_links = self.checks[u].iter().all(|&(v, direction, elabel)| {
+            let y = image(v);
+            match direction {
+                Direction::Out => has_arc(x, y, elabel),
+                Direction::In => has_arc(y, x, elabel),
+                Direction::Either => has_arc(x, y, elabel) || has_arc(y, x, elabel),
+            }
+        });
+        if !preserves_links {
+            return false;
+        }
+        match self.semantics {
+            Semantics::Homomorphism | Semantics::EdgeIsomorphism => true,
+            Semantics::VertexIsomorphism => images.iter().all(|&image| image != Some(x)),
+            Semantics::Induced => images.iter().enumerate().all(|(v, &image)| match image {
+                Some(y) => y != x && (self.adjacent[u][v] || !self.is_adjacent(x, y)),
+                None => true,
+            }),
+        }
+    }
+
+    fn is_adjacent(&self, x: usize, y: usize) -> bool {
+        let has_arc = |i, j| self.graph.out_arcs(i).iter().any(|&(other, _)| other == j);
+        has_arc(x, y) || has_arc(y, x)
+    }
+
+    fn satisfies_constraint(&self, images: &[usize]) -> bool {
+        let indices: HashMap<VId, usize> = self
+            .ast
+            .vertices()
+            .iter()
+            .enumerate()
+            .map(|(i, &(vid, _))| (vid, images[i]))
+            .collect();
+        match self.ast.constraint() {
+            Some(expr) => {
+                expr.eval(&|vid| self.graph.vid(indices[&vid]) as i64, &|vid| {
+                    self.graph.vlabel(indices[&vid]) as i64
+                }) != 0
+            }
+            None => true,
+        }
+    }
+
+    /// Counts the mappings of the pattern arcs and edges to data arcs, as `(src, k)` for the
+    /// `k`th arc of `graph.out_arcs(src)`, which are distinct under edge isomorphisms.
+    fn count_arc_mappings(&self, images: &[usize]) -> usize {
+        let index = |vid: &VId| {
+            self.ast
+                .vertices()
+                .iter()
+                .position(|(other, _)| other == vid)
+                .unwrap()
+        };
+        let arcs = |i: usize, j: usize, elabel: ELabel| {
+            self.graph
+                .out_arcs(i)
+                .iter()
+                .enumerate()
+                .filter(move |&(_, &arc)| arc == (j, elabel))
+                .map(move |(k, _)| (i, k))
+        };
+        let mut choices: Vec<Vec<(usize, usize)>> = Vec::new();
+        for &(src, dst, elabel) in self.ast.arcs() {
+            choices.push(arcs(images[index(&src)], images[index(&dst)], elabel).collect());
+        }
+        for &(src, dst, elabel) in self.ast.edges() {
+            let (i, j) = (images[index(&src)], images[index(&dst)]);
+            let mut both: Vec<(usize, usize)> = arcs(i, j, elabel).collect();
+            if i != j {
+                both.extend(arcs(j, i, elabel));
+            }
+            choices.push(both);
+        }
+        fn assign(
+            choices: &[Vec<(usize, usize)>],
+            used: &mut Vec<(usize, usize)>,
+            distinct: bool,
+        ) -> usize {
+            match choices.split_first() {
+                Some((first, rest)) => first
+                    .iter()
+                    .map(|&arc| {
+                        if distinct && used.contains(&arc) {
+                            return 0;
+                        }
+                        used.push(arc);
+                        let count = assign(rest, used, distinct);
+                        used.pop();
+                        count
+                    })
+                    .sum(),
+                None => 1,
+            }
+        }
+        let distinct = self.semantics == Semantics::EdgeIsomorphism;
+        assign(&choices, &mut Vec::with_capacity(choices.len()), distinct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_graph::write_sqlite3,
+        pattern_graph::{find_sql_matches, parse},
+    };
+
+    #[test]
+    fn test_find_matches() {
+        // A directed triangle 1 -> 2 -> 3 -> 1 with a chord 1 -> 3, and a pendant 3 -> 4.
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 0), (2, 0), (3, 0), (4, 1)],
+            vec![(1, 2, 0), (2, 3, 0), (3, 1, 0), (1, 3, 0), (3, 4, 0)],
+        )
+        .unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        let path =
+            parse("(match (vertices (u1 0) (u2 0) (u3 0)) (arcs (u1 u2 0) (u2 u3 0)))").unwrap();
+        let count = |ast, semantics| count_matches(ast, &graph, semantics);
+        assert_eq!(count(&path, Semantics::Homomorphism), 5);
+        assert_eq!(count(&path, Semantics::EdgeIsomorphism), 5);
+        assert_eq!(count(&path, Semantics::VertexIsomorphism), 3);
+        assert_eq!(count(&path, Semantics::Induced), 0);
+
+        let edge =
+            parse("(match (vertices (u1 0) (u2 1)) (edges (u1 u2 0)) (where (< u1 4)))").unwrap();
+        let mut matches = Vec::new();
+        let num_matches = find_matches(&edge, &graph, Semantics::Homomorphism, &mut |vids| {
+            matches.push(vids.to_vec())
+        });
+        assert_eq!(num_matches, 1);
+        assert_eq!(matches, vec![vec![3, 4]]);
+
+        let wedge = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0)) (edges (u1 u2 0) (u1 u3 0)) \
+             (where (<= u2 u3)))",
+        )
+        .unwrap();
+        assert_eq!(count(&wedge, Semantics::Homomorphism), 9);
+        assert_eq!(count(&wedge, Semantics::EdgeIsomorphism), 5);
+        assert_eq!(count(&wedge, Semantics::VertexIsomorphism), 3);
+    }
+
+    #[test]
+    fn test_count_arc_matches() {
+        // Antiparallel arcs 1 <-> 2 and parallel arcs 2 -> 3, all labelled 0.
+        let conn = sqlite::open(":memory:").unwrap();
+        write_sqlite3(
+            &conn,
+            vec![(1, 0), (2, 0), (3, 0)],
+            vec![(1, 2, 0), (2, 1, 0), (2, 3, 0), (2, 3, 0)],
+        )
+        .unwrap();
+        let graph = Graph::from_sqlite3(&conn).unwrap();
+        let edge = parse("(match (vertices (u1 0) (u2 0)) (edges (u1 u2 0)))").unwrap();
+        let arc = parse("(match (vertices (u1 0) (u2 0)) (arcs (u1 u2 0)))").unwrap();
+        let cycle =
+            parse("(match (vertices (u1 0) (u2 0)) (arcs (u1 u2 0)) (edges (u1 u2 0)))").unwrap();
+        for &(ast, semantics, num_matches, num_arc_matches) in &[
+            // (1, 2), (2, 1), (2, 3) and (3, 2).
+            (&edge, Semantics::Homomorphism, 4, 8),
+            (&edge, Semantics::EdgeIsomorphism, 4, 8),
+            // (1, 2), (2, 1) and (2, 3).
+            (&arc, Semantics::Homomorphism, 3, 4),
+            // The edge may only take another arc than the arc.
+            (&cycle, Semantics::Homomorphism, 3, 8),
+            (&cycle, Semantics::EdgeIsomorphism, 3, 4),
+        ] {
+            let sql_count = find_sql_matches(&conn, ast, Some(semantics), &mut |_| ()).unwrap();
+            assert_eq!(count_matches(ast, &graph, semantics), num_matches);
+            assert_eq!(sql_count, num_matches);
+            assert_eq!(count_arc_matches(ast, &graph, semantics), num_arc_matches);
+        }
+    }
+}
@@ -1,11 +1,29 @@
-pub use ast::{Ast, Expr};
+pub use ast::{Ast, CmpOp, Expr, UnsupportedExpr};
+pub use automorphism::{automorphisms, break_symmetry, count_automorphisms, symmetry_breaking};
 pub use gisp_to_cypher::gisp_to_cypher;
-pub use gisp_to_gisp::{gisp_to_gisp, gisp_to_star};
-pub use gisp_to_graphflow::gisp_to_graphflow;
-pub use parser::parse;
+pub use gisp_to_datalog::gisp_to_datalog;
+pub use gisp_to_gisp::{gisp_to_gisp, gisp_to_gisp_embedded, gisp_to_star, EmbeddingError};
+pub use gisp_to_graph::{gisp_to_graph, UnsupportedGraph};
+pub use gisp_to_graphflow::{gisp_to_graphflow, UnsupportedGraphflow};
+pub use gisp_to_sparql::gisp_to_sparql;
+pub use gisp_to_sql::{find_sql_matches, gisp_to_sql};
+pub use graph_to_gisp::graph_to_gisp;
+pub use matcher::{count_arc_matches, count_matches, find_matches, Semantics};
+pub use parser::{parse, GispError, Position};
+pub use stats::{stats, Stats};
+pub use validate::{lint, validate, Diagnostic, Target};
 
 mod ast;
+mod automorphism;
 mod gisp_to_cypher;
+mod gisp_to_datalog;
 mod gisp_to_gisp;
+mod gisp_to_graph;
 mod gisp_to_graphflow;
+mod gisp_to_sparql;
+mod gisp_to_sql;
+mod graph_to_gisp;
+mod matcher;
 mod parser;
+mod stats;
+mod validate;
@@ -1,57 +1,160 @@
 use crate::{
-    pattern_graph::Ast,
+    pattern_graph::{Ast, CmpOp, Expr},
     types::{ELabel, VId, VLabel},
 };
-use pest::Parser as PestParser;
+use derive_more::Display;
+use pest::{iterators::Pair, Parser as PestParser};
 use pest_derive::Parser;
+use std::{collections::HashSet, str::FromStr};
 
 #[derive(Parser)]
 #[grammar = "pattern_graph/gisp.pest"]
 struct Parser;
 
-pub fn parse(source: &str) -> Result<Ast, pest::error::Error<Rule>> {
-    Ok(Parser::parse(Rule::Query, source)?
-        .next()
-        .map(|query| query_to_ast(query))
-        .unwrap())
+/// Line and column of a token, both starting from 1.
+#[derive(Debug, Display, Clone, Copy, PartialEq)]
+#[display(fmt = "{}:{}", line, col)]
+pub struct Position {
+    pub line: usize,
+    pub col: usize,
 }
 
-fn query_to_ast(pair: pest::iterators::Pair<Rule>) -> Ast {
-    let (mut vertices, mut arcs, mut edges, constraint) = (vec![], vec![], vec![], None);
+impl Position {
+    fn of(pair: &Pair<Rule>) -> Self {
+        let (line, col) = pair.as_span().start_pos().line_col();
+        Self { line, col }
+    }
+}
+
+#[derive(Debug, Display, PartialEq)]
+pub enum GispError {
+    #[display(fmt = "{}", _0)]
+    Syntax(Box<pest::error::Error<Rule>>),
+    #[display(fmt = "{}: `{}` is out of range", _0, _1)]
+    Overflow(Position, String),
+    #[display(fmt = "{}: u{} is declared more than once", _0, _1)]
+    DuplicateVertex(Position, VId),
+    #[display(fmt = "{}: u{} is not declared", _0, _1)]
+    DanglingVertex(Position, VId),
+}
+
+impl std::error::Error for GispError {}
+
+impl From<pest::error::Error<Rule>> for GispError {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        GispError::Syntax(Box::new(error))
+    }
+}
+
+pub fn parse(source: &str) -> Result<Ast, GispError> {
+    query_to_ast(Parser::parse(Rule::Query, source)?.next().unwrap())
+}
+
+fn query_to_ast(pair: Pair<Rule>) -> Result<Ast, GispError> {
+    let (mut vertices, mut arcs, mut edges, mut constraint) = (vec![], vec![], vec![], None);
+    let mut vids = HashSet::new();
     for pair in pair.into_inner() {
         match pair.as_rule() {
             Rule::Vertices => {
                 for vertex in pair.into_inner() {
                     let mut pair = vertex.into_inner();
-                    let vid: VId = pair.next().unwrap().as_str()[1..].parse().unwrap();
-                    let vlabel: VLabel = pair.next().unwrap().as_str().parse().unwrap();
+                    let vid_pair = pair.next().unwrap();
+                    let vid = parse_vid(&vid_pair)?;
+                    if !vids.insert(vid) {
+                        return Err(GispError::DuplicateVertex(Position::of(&vid_pair), vid));
+                    }
+                    let vlabel: VLabel = parse_number(&pair.next().unwrap())?;
                     vertices.push((vid, vlabel))
                 }
             }
             Rule::Arcs => {
                 for arc in pair.into_inner() {
-                    let mut pair = arc.into_inner();
-                    let src: VId = pair.next().unwrap().as_str()[1..].parse().unwrap();
-                    let dst: VId = pair.next().unwrap().as_str()[1..].parse().unwrap();
-                    let elabel: ELabel = pair.next().unwrap().as_str().parse().unwrap();
-                    arcs.push((src, dst, elabel))
+                    arcs.push(edge_to_ast(arc, &vids)?);
                 }
             }
             Rule::Edges => {
                 for edge in pair.into_inner() {
-                    let mut pair = edge.into_inner();
-                    let src: VId = pair.next().unwrap().as_str()[1..].parse().unwrap();
-                    let dst: VId = pair.next().unwrap().as_str()[1..].parse().unwrap();
-                    let elabel: ELabel = pair.next().unwrap().as_str().parse().unwrap();
-                    edges.push((src, dst, elabel))
+                    edges.push(edge_to_ast(edge, &vids)?);
                 }
             }
-            Rule::Where => todo!(),
+            Rule::Where => {
+                constraint = Some(expr_to_ast(pair.into_inner().next().unwrap(), &vids)?)
+            }
             Rule::EOI => (),
             _ => unreachable!(),
         }
     }
-    Ast::new(vertices, arcs, edges, constraint)
+    Ok(Ast::new(vertices, arcs, edges, constraint))
+}
+
+fn edge_to_ast(pair: Pair<Rule>, vids: &HashSet<VId>) -> Result<(VId, VId, ELabel), GispError> {
+    let mut pair = pair.into_inner();
+    let src = parse_declared_vid(&pair.next().unwrap(), vids)?;
+    let dst = parse_declared_vid(&pair.next().unwrap(), vids)?;
+    let elabel: ELabel = parse_number(&pair.next().unwrap())?;
+    Ok((src, dst, elabel))
+}
+
+fn expr_to_ast(pair: Pair<Rule>, vids: &HashSet<VId>) -> Result<Expr, GispError> {
+    Ok(match pair.as_rule() {
+        Rule::Int => Expr::Int(parse_number(&pair)?),
+        Rule::VId => Expr::VId(parse_declared_vid(&pair, vids)?),
+        Rule::Label => Expr::VLabel(parse_declared_vid(
+            &pair.into_inner().next().unwrap(),
+            vids,
+        )?),
+        Rule::Not => Expr::Not(Box::new(expr_to_ast(
+            pair.into_inner().next().unwrap(),
+            vids,
+        )?)),
+        Rule::And => Expr::And(
+            pair.into_inner()
+                .map(|pair| expr_to_ast(pair, vids))
+                .collect::<Result<_, _>>()?,
+        ),
+        Rule::Or => Expr::Or(
+            pair.into_inner()
+                .map(|pair| expr_to_ast(pair, vids))
+                .collect::<Result<_, _>>()?,
+        ),
+        Rule::Cmp => {
+            let mut pair = pair.into_inner();
+            let op = match pair.next().unwrap().as_str() {
+                "=" => CmpOp::Eq,
+                "!=" => CmpOp::Ne,
+                "<" => CmpOp::Lt,
+                "<=" => CmpOp::Le,
+                ">" => CmpOp::Gt,
+                ">=" => CmpOp::Ge,
+                _ => unreachable!(),
+            };
+            let lhs = expr_to_ast(pair.next().unwrap(), vids)?;
+            let rhs = expr_to_ast(pair.next().unwrap(), vids)?;
+            Expr::Cmp(op, Box::new(lhs), Box::new(rhs))
+        }
+        _ => unreachable!(),
+    })
+}
+
+fn parse_number<T: FromStr>(pair: &Pair<Rule>) -> Result<T, GispError> {
+    pair.as_str()
+        .parse()
+        .map_err(|_| GispError::Overflow(Position::of(pair), pair.as_str().to_string()))
+}
+
+fn parse_vid(pair: &Pair<Rule>) -> Result<VId, GispError> {
+    pair.as_str()[1..]
+        .parse()
+        .map_err(|_| GispError::Overflow(Position::of(pair), pair.as_str().to_string()))
+}
+
+fn parse_declared_vid(pair: &Pair<Rule>, vids: &HashSet<VId>) -> Result<VId, GispError> {
+    let vid = parse_vid(pair)?;
+    if vids.contains(&vid) {
+        Ok(vid)
+    } else {
+        Err(GispError::DanglingVertex(Position::of(pair), vid))
+    }
 }
 
 #[cfg(test)]
@@ -75,4 +178,63 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_where() {
+        let source = "\
+(match (vertices (u1 1) (u2 2) (u3 3) (u4 4))
+       (arcs (u1 u2 12) (u1 u3 13) (u1 u4 14))
+       (where (and (< u1 u2) (or (= (label u3) 5) (not (!= u1 u4))) (>= u2 -1))))";
+        let ast = parse(source).unwrap();
+        assert_eq!(
+            ast.constraint(),
+            Some(&Expr::And(vec![
+                Expr::Cmp(CmpOp::Lt, Box::new(Expr::VId(1)), Box::new(Expr::VId(2))),
+                Expr::Or(vec![
+                    Expr::Cmp(CmpOp::Eq, Box::new(Expr::VLabel(3)), Box::new(Expr::Int(5))),
+                    Expr::Not(Box::new(Expr::Cmp(
+                        CmpOp::Ne,
+                        Box::new(Expr::VId(1)),
+                        Box::new(Expr::VId(4))
+                    ))),
+                ]),
+                Expr::Cmp(CmpOp::Ge, Box::new(Expr::VId(2)), Box::new(Expr::Int(-1))),
+            ]))
+        );
+        assert_eq!(parse(&ast.to_string()).unwrap(), ast);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse("(match (vertices (u1 1) (u2 2))"),
+            Err(GispError::Syntax(_))
+        ));
+        assert_eq!(
+            parse("(match (vertices (u99999999999 1)))"),
+            Err(GispError::Overflow(
+                Position { line: 1, col: 19 },
+                "u99999999999".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("(match (vertices (u1 40000)))"),
+            Err(GispError::Overflow(
+                Position { line: 1, col: 22 },
+                "40000".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("(match (vertices (u1 1)\n                 (u1 2)))"),
+            Err(GispError::DuplicateVertex(Position { line: 2, col: 19 }, 1))
+        );
+        assert_eq!(
+            parse("(match (vertices (u1 1) (u2 2)) (arcs (u1 u3 0)))"),
+            Err(GispError::DanglingVertex(Position { line: 1, col: 43 }, 3))
+        );
+        assert_eq!(
+            parse("(match (vertices (u1 1) (u2 2)) (arcs (u1 u2 0)) (where (< u1 u4)))"),
+            Err(GispError::DanglingVertex(Position { line: 1, col: 63 }, 4))
+        );
+    }
 }
@@ -0,0 +1,332 @@
+//! Statistics of patterns
+
+use crate::{
+    pattern_graph::{automorphism::count_automorphisms, Ast},
+    types::VId,
+};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet, VecDeque};
+
+/// Statistics of a pattern.
+///
+/// Shape properties are those of the underlying simple undirected graph, i.e. arc directions
+/// and parallel edges are ignored, while self-loops make a pattern cyclic.  Arcs and edges with
+/// an undeclared vertex, which `validate` reports, are ignored too.
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub num_vertices: usize,
+    pub num_arcs: usize,
+    pub num_edges: usize,
+    /// Degrees in descending order.
+    pub degrees: Vec<usize>,
+    pub num_vlabels: usize,
+    pub num_elabels: usize,
+    pub connected: bool,
+    pub cyclic: bool,
+    pub tree: bool,
+    pub clique: bool,
+    pub star: bool,
+    pub path: bool,
+    /// `None` if the pattern is disconnected.
+    pub diameter: Option<usize>,
+    pub num_automorphisms: usize,
+    pub core_numbers: Vec<(VId, usize)>,
+    /// Vertices of the 2-core.
+    pub core: Vec<VId>,
+    /// Vertices out of the 2-core, except leaves.
+    pub forest: Vec<VId>,
+    /// Vertices of degree 1 out of the 2-core.
+    pub leaves: Vec<VId>,
+}
+
+pub fn stats(ast: &Ast) -> Stats {
+    let mut neighbors: BTreeMap<VId, BTreeSet<VId>> = ast
+        .vertices()
+        .iter()
+        .map(|&(vid, _)| (vid, BTreeSet::new()))
+        .collect();
+    let mut self_loop = false;
+    for &(src, dst, _) in ast.arcs().iter().chain(ast.edges()) {
+        if !neighbors.contains_key(&src) || !neighbors.contains_key(&dst) {
+            continue;
+        }
+        if src == dst {
+            self_loop = true;
+        } else {
+            neighbors.get_mut(&src).unwrap().insert(dst);
+            neighbors.get_mut(&dst).unwrap().insert(src);
+        }
+    }
+    let num_vertices = neighbors.len();
+    let num_pairs = neighbors.values().map(|vids| vids.len()).sum::<usize>() / 2;
+    let mut degrees: Vec<usize> = neighbors.values().map(|vids| vids.len()).collect();
+    degrees.sort_unstable_by(|a, b| b.cmp(a));
+    let max_degree = degrees.first().cloned().unwrap_or(0);
+    let num_components = count_components(&neighbors);
+    let connected = num_components == 1;
+    let cyclic = self_loop || num_pairs + num_components > num_vertices;
+    let tree = connected && !cyclic;
+    let core_numbers = core_numbers(&neighbors);
+    let core: Vec<VId> = core_numbers
+        .iter()
+        .filter(|&&(_, k)| k >= 2)
+        .map(|&(vid, _)| vid)
+        .collect();
+    let (leaves, forest): (Vec<VId>, Vec<VId>) = core_numbers
+        .iter()
+        .filter(|&&(_, k)| k < 2)
+        .map(|&(vid, _)| vid)
+        .partition(|vid| neighbors[vid].len() == 1);
+    Stats {
+        num_vertices,
+        num_arcs: ast.arcs().len(),
+        num_edges: ast.edges().len(),
+        degrees,
+        num_vlabels: ast
+            .vertices()
+            .iter()
+            .map(|&(_, vlabel)| vlabel)
+            .collect::<HashSet<_>>()
+            .len(),
+        num_elabels: ast
+            .arcs()
+            .iter()
+            .chain(ast.edges())
+            .map(|&(_, _, elabel)| elabel)
+            .collect::<HashSet<_>>()
+            .len(),
+        connected,
+        cyclic,
+        tree,
+        clique: !self_loop && 2 * num_pairs == num_vertices * num_vertices.saturating_sub(1),
+        star: tree && num_vertices >= 2 && max_degree == num_vertices - 1,
+        path: tree && max_degree <= 2,
+        diameter: if connected {
+            neighbors
+                .keys()
+                .map(|&vid| eccentricity(&neighbors, vid))
+                .max()
+        } else {
+            None
+        },
+        num_automorphisms: count_automorphisms(ast),
+        core_numbers,
+        core,
+        forest,
+        leaves,
+    }
+}
+
+fn count_components(neighbors: &BTreeMap<VId, BTreeSet<VId>>) -> usize {
+    let mut visited = HashSet::with_capacity(neighbors.len());
+    let mut count = 0;
+    for &vid in neighbors.keys() {
+        if visited.insert(vid) {
+            count += 1;
+            let mut stack = vec![vid];
+            while let Some(u) = stack.pop() {
+                for &v in &neighbors[&u] {
+                    if visited.insert(v) {
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+    }
+    count
+}
+
+fn eccentricity(neighbors: &BTreeMap<VId, BTreeSet<VId>>, root: VId) -> usize {
+    let mut distances = BTreeMap::new();
+    distances.insert(root, 0);
+    let mut queue = VecDeque::from(vec![root]);
+    let mut max_distance = 0;
+    while let Some(u) = queue.pop_front() {
+        let distance = distances[&u] + 1;
+        for &v in &neighbors[&u] {
+            if let Entry::Vacant(entry) = distances.entry(v) {
+                entry.insert(distance);
+                max_distance = distance;
+                queue.push_back(v);
+            }
+        }
+    }
+    max_distance
+}
+
+/// Computes core numbers by repeatedly peeling a vertex of minimum degree.
+fn core_numbers(neighbors: &BTreeMap<VId, BTreeSet<VId>>) -> Vec<(VId, usize)> {
+    let mut degrees: BTreeMap<VId, usize> = neighbors
+        .iter()
+        .map(|(&vid, vids)| (vid, vids.len()))
+        .collect();
+    let mut core_numbers = BTreeMap::new();
+    let mut k = 0;
+    while let Some((&vid, &degree)) = degrees.iter().min_by_key(|&(_, &degree)| degree) {
+        k = k.max(degree);
+        core_numbers.insert(vid, k);
+        degrees.remove(&vid);
+        for v in &neighbors[&vid] {
+            if let Some(degree) = degrees.get_mut(v) {
+                *degree -= 1;
+            }
+        }
+    }
+    core_numbers.into_iter().collect()
+}
+
+fn join<T: ToString>(items: &[T], sep: &str) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+impl Stats {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"num_vertices\": {}, \"num_arcs\": {}, \"num_edges\": {}, \"degrees\": [{}], \
+             \"num_vlabels\": {}, \"num_elabels\": {}, \"connected\": {}, \"cyclic\": {}, \
+             \"tree\": {}, \"clique\": {}, \"star\": {}, \"path\": {}, \"diameter\": {}, \
+             \"num_automorphisms\": {}, \"core_numbers\": [{}], \"core\": [{}], \
+             \"forest\": [{}], \"leaves\": [{}]}}",
+            self.num_vertices,
+            self.num_arcs,
+            self.num_edges,
+            join(&self.degrees, ", "),
+            self.num_vlabels,
+            self.num_elabels,
+            self.connected,
+            self.cyclic,
+            self.tree,
+            self.clique,
+            self.star,
+            self.path,
+            self.diameter
+                .map_or("null".to_string(), |diameter| diameter.to_string()),
+            self.num_automorphisms,
+            self.core_numbers
+                .iter()
+                .map(|(vid, k)| format!("[{}, {}]", vid, k))
+                .collect::<Vec<_>>()
+                .join(", "),
+            join(&self.core, ", "),
+            join(&self.forest, ", "),
+            join(&self.leaves, ", "),
+        )
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let vids = |vids: &[VId]| {
+            vids.iter()
+                .map(|vid| format!("u{}", vid))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(f, "num_vertices: {}", self.num_vertices)?;
+        writeln!(f, "num_arcs: {}", self.num_arcs)?;
+        writeln!(f, "num_edges: {}", self.num_edges)?;
+        writeln!(f, "degrees: {}", join(&self.degrees, " "))?;
+        writeln!(f, "num_vlabels: {}", self.num_vlabels)?;
+        writeln!(f, "num_elabels: {}", self.num_elabels)?;
+        writeln!(f, "connected: {}", self.connected)?;
+        writeln!(f, "cyclic: {}", self.cyclic)?;
+        writeln!(f, "tree: {}", self.tree)?;
+        writeln!(f, "clique: {}", self.clique)?;
+        writeln!(f, "star: {}", self.star)?;
+        writeln!(f, "path: {}", self.path)?;
+        match self.diameter {
+            Some(diameter) => writeln!(f, "diameter: {}", diameter)?,
+            None => writeln!(f, "diameter: inf")?,
+        }
+        writeln!(f, "num_automorphisms: {}", self.num_automorphisms)?;
+        writeln!(
+            f,
+            "core_numbers: {}",
+            self.core_numbers
+                .iter()
+                .map(|(vid, k)| format!("u{}:{}", vid, k))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )?;
+        writeln!(f, "core: {}", vids(&self.core))?;
+        writeln!(f, "forest: {}", vids(&self.forest))?;
+        write!(f, "leaves: {}", vids(&self.leaves))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_graph::parse;
+
+    #[test]
+    fn test_stats() {
+        let ast = parse(
+            "(match (vertices (u1 0) (u2 0) (u3 0) (u4 1) (u5 1)) \
+             (arcs (u1 u2 0) (u2 u3 0) (u3 u1 0)) (edges (u3 u4 1) (u4 u5 1)))",
+        )
+        .unwrap();
+        let stats = stats(&ast);
+        assert_eq!(
+            stats,
+            Stats {
+                num_vertices: 5,
+                num_arcs: 3,
+                num_edges: 2,
+                degrees: vec![3, 2, 2, 2, 1],
+                num_vlabels: 2,
+                num_elabels: 2,
+                connected: true,
+                cyclic: true,
+                tree: false,
+                clique: false,
+                star: false,
+                path: false,
+                diameter: Some(3),
+                num_automorphisms: 1,
+                core_numbers: vec![(1, 2), (2, 2), (3, 2), (4, 1), (5, 1)],
+                core: vec![1, 2, 3],
+                forest: vec![4],
+                leaves: vec![5],
+            }
+        );
+        assert_eq!(
+            stats.to_json(),
+            "{\"num_vertices\": 5, \"num_arcs\": 3, \"num_edges\": 2, \"degrees\": [3, 2, 2, 2, 1], \
+             \"num_vlabels\": 2, \"num_elabels\": 2, \"connected\": true, \"cyclic\": true, \
+             \"tree\": false, \"clique\": false, \"star\": false, \"path\": false, \"diameter\": 3, \
+             \"num_automorphisms\": 1, \"core_numbers\": [[1, 2], [2, 2], [3, 2], [4, 1], [5, 1]], \
+             \"core\": [1, 2, 3], \"forest\": [4], \"leaves\": [5]}"
+        );
+    }
+
+    #[test]
+    fn test_shapes() {
+        let star = stats(
+            &parse("(match (vertices (u1 0) (u2 0) (u3 0) (u4 0)) (edges (u1 u2 0) (u1 u3 0) (u1 u4 0)))")
+                .unwrap(),
+        );
+        assert!(star.tree && star.star && !star.path && !star.clique);
+        assert_eq!(star.num_automorphisms, 6);
+        let triangle = stats(
+            &parse("(match (vertices (u1 0) (u2 0) (u3 0)) (edges (u1 u2 0) (u2 u3 0) (u1 u3 0)))")
+                .unwrap(),
+        );
+        assert!(triangle.clique && triangle.cyclic && !triangle.tree);
+        assert_eq!(triangle.diameter, Some(1));
+        let disconnected = stats(&parse("(match (vertices (u1 0) (u2 0)))").unwrap());
+        assert!(!disconnected.connected && !disconnected.tree);
+        assert_eq!(disconnected.diameter, None);
+        let dangling = stats(&Ast::new(
+            vec![(1, 0), (2, 0)],
+            vec![(1, 2, 0), (1, 3, 0)],
+            vec![(3, 3, 0)],
+            None,
+        ));
+        assert!(dangling.path && !dangling.cyclic);
+        assert_eq!(dangling.degrees, vec![1, 1]);
+    }
+}
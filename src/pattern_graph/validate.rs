@@ -0,0 +1,253 @@
+use crate::{
+    pattern_graph::{
+        gisp_to_cypher, gisp_to_graphflow, parse, Ast, GispError, UnsupportedExpr,
+        UnsupportedGraphflow,
+    },
+    types::VId,
+};
+use derive_more::Display;
+use std::collections::{HashMap, HashSet};
+
+/// Engines the pattern is going to be translated for.
+#[derive(Debug, Display, Clone, Copy, PartialEq)]
+pub enum Target {
+    #[display(fmt = "cypher")]
+    Cypher,
+    #[display(fmt = "graphflow")]
+    Graphflow,
+}
+
+#[derive(Debug, Display, PartialEq)]
+pub enum Diagnostic {
+    #[display(fmt = "{}", _0)]
+    Parse(GispError),
+    #[display(fmt = "u{} is declared more than once", _0)]
+    DuplicateVertex(VId),
+    #[display(fmt = "u{} is not declared", _0)]
+    DanglingVertex(VId),
+    #[display(fmt = "self-loop on u{}", _0)]
+    SelfLoop(VId),
+    #[display(fmt = "parallel edges with the same label between u{} and u{}", _0, _1)]
+    ParallelEdge(VId, VId),
+    #[display(fmt = "pattern has {} connected components", _0)]
+    Disconnected(usize),
+    #[display(fmt = "{} cannot match isolated vertex u{}", _0, _1)]
+    IsolatedVertex(Target, VId),
+    #[display(fmt = "{}", _0)]
+    UnsupportedExpr(UnsupportedExpr),
+    #[display(fmt = "{}", _0)]
+    UnsupportedGraphflow(UnsupportedGraphflow),
+}
+
+/// Parses and checks the source of a pattern, reporting parse errors as diagnostics.
+pub fn lint(source: &str, target: Option<Target>) -> Vec<Diagnostic> {
+    match parse(source) {
+        Ok(ast) => validate(&ast, target),
+        Err(error) => vec![Diagnostic::Parse(error)],
+    }
+}
+
+/// Checks the pattern, and optionally the constructs supported by `target`.
+///
+/// Duplicate and undeclared vertices are rejected by `parse` already, so they are only reported
+/// for patterns built with `Ast::new`.
+pub fn validate(ast: &Ast, target: Option<Target>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut vids = HashSet::with_capacity(ast.vertices().len());
+    for &(vid, _) in ast.vertices() {
+        if !vids.insert(vid) {
+            diagnostics.push(Diagnostic::DuplicateVertex(vid));
+        }
+    }
+    let mut dangling = HashSet::new();
+    let referenced = ast
+        .arcs()
+        .iter()
+        .chain(ast.edges())
+        .flat_map(|&(src, dst, _)| vec![src, dst])
+        .chain(ast.constraint().map(|expr| expr.vids()).unwrap_or_default());
+    for vid in referenced {
+        if !vids.contains(&vid) && dangling.insert(vid) {
+            diagnostics.push(Diagnostic::DanglingVertex(vid));
+        }
+    }
+    let mut pairs = HashSet::with_capacity(ast.arcs().len() + ast.edges().len());
+    for (&(src, dst, elabel), directed) in ast
+        .arcs()
+        .iter()
+        .map(|arc| (arc, true))
+        .chain(ast.edges().iter().map(|edge| (edge, false)))
+    {
+        if src == dst {
+            diagnostics.push(Diagnostic::SelfLoop(src));
+        }
+        let pair = if directed || src <= dst {
+            (directed, src, dst, elabel)
+        } else {
+            (directed, dst, src, elabel)
+        };
+        if !pairs.insert(pair) {
+            diagnostics.push(Diagnostic::ParallelEdge(src, dst));
+        }
+    }
+    let num_components = count_components(ast);
+    if num_components > 1 {
+        diagnostics.push(Diagnostic::Disconnected(num_components));
+    }
+    // The translators assume every referenced vertex is declared exactly once.
+    if !dangling.is_empty()
+        || diagnostics
+            .iter()
+            .any(|diagnostic| matches!(diagnostic, Diagnostic::DuplicateVertex(_)))
+    {
+        return diagnostics;
+    }
+    match target {
+        Some(Target::Cypher) => {
+            if let Err(error) = gisp_to_cypher(ast, None) {
+                diagnostics.push(Diagnostic::UnsupportedExpr(error));
+            }
+        }
+        Some(Target::Graphflow) => {
+            let connected: HashSet<VId> = ast
+                .arcs()
+                .iter()
+                .chain(ast.edges())
+                .flat_map(|&(src, dst, _)| vec![src, dst])
+                .collect();
+            for &(vid, _) in ast.vertices() {
+                if !connected.contains(&vid) {
+                    diagnostics.push(Diagnostic::IsolatedVertex(Target::Graphflow, vid));
+                }
+            }
+            if let Some(expr) = ast.constraint() {
+                let vid_vlabels = ast.vertices().iter().cloned().collect();
+                if let Err(error) = expr.check_implied("graphflow", &vid_vlabels) {
+                    diagnostics.push(Diagnostic::UnsupportedExpr(error));
+                }
+            }
+            // Without the constraint, so that its errors do not hide the others.
+            let unconstrained = Ast::new(
+                ast.vertices().to_vec(),
+                ast.arcs().to_vec(),
+                ast.edges().to_vec(),
+                None,
+            );
+            match gisp_to_graphflow(&unconstrained, None) {
+                // Isolated vertices are all reported above.
+                Ok(_) | Err(UnsupportedGraphflow::IsolatedVertex(_)) => (),
+                Err(error) => diagnostics.push(Diagnostic::UnsupportedGraphflow(error)),
+            }
+        }
+        None => (),
+    }
+    diagnostics
+}
+
+fn count_components(ast: &Ast) -> usize {
+    let mut parents: HashMap<VId, VId> =
+        ast.vertices().iter().map(|&(vid, _)| (vid, vid)).collect();
+    fn find(parents: &mut HashMap<VId, VId>, vid: VId) -> VId {
+        let parent = parents[&vid];
+        if parent == vid {
+            vid
+        } else {
+            let root = find(parents, parent);
+            parents.insert(vid, root);
+            root
+        }
+    }
+    for &(src, dst, _) in ast.arcs().iter().chain(ast.edges()) {
+        if parents.contains_key(&src) && parents.contains_key(&dst) {
+            let (src, dst) = (find(&mut parents, src), find(&mut parents, dst));
+            parents.insert(src, dst);
+        }
+    }
+    let vids: Vec<VId> = parents.keys().cloned().collect();
+    vids.into_iter()
+        .map(|vid| find(&mut parents, vid))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let ast = Ast::new(
+            vec![(1, 0), (2, 0), (2, 0), (3, 0), (4, 0)],
+            vec![(1, 2, 0), (1, 2, 1), (3, 3, 0), (1, 5, 0), (1, 2, 0)],
+            vec![(2, 1, 0), (1, 2, 0), (1, 2, 1)],
+            None,
+        );
+        assert_eq!(
+            validate(&ast, Some(Target::Cypher)),
+            vec![
+                Diagnostic::DuplicateVertex(2),
+                Diagnostic::DanglingVertex(5),
+                Diagnostic::SelfLoop(3),
+                Diagnostic::ParallelEdge(1, 2),
+                Diagnostic::ParallelEdge(1, 2),
+                Diagnostic::Disconnected(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        assert_eq!(
+            lint("(match (vertices (u1 0) (u2 0)) (arcs (u1 u2 0)))", None),
+            vec![]
+        );
+        let source = "(match (vertices (u1 0) (u1 0)))";
+        assert_eq!(
+            lint(source, None),
+            vec![Diagnostic::Parse(parse(source).unwrap_err())]
+        );
+    }
+
+    #[test]
+    fn test_lint_too_many_edges() {
+        let vertices: Vec<String> = (1..=18).map(|vid| format!("(u{} 0)", vid)).collect();
+        let edges: Vec<String> = (1..=17)
+            .map(|vid| format!("(u{} u{} 0)", vid, vid + 1))
+            .collect();
+        let source = format!(
+            "(match (vertices {}) (edges {}) (where (< u1 u2)))",
+            vertices.join(" "),
+            edges.join(" ")
+        );
+        let diagnostics = lint(&source, Some(Target::Graphflow));
+        assert_eq!(diagnostics.len(), 2);
+        assert!(matches!(diagnostics[0], Diagnostic::UnsupportedExpr(_)));
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic::UnsupportedGraphflow(UnsupportedGraphflow::TooManyEdges(17))
+        );
+    }
+
+    #[test]
+    fn test_validate_target() {
+        let ast =
+            parse("(match (vertices (u1 1) (u2 2) (u3 3)) (arcs (u1 u2 0)) (where (< u1 u2)))")
+                .unwrap();
+        assert_eq!(validate(&ast, None), vec![Diagnostic::Disconnected(2)]);
+        assert_eq!(
+            validate(&ast, Some(Target::Cypher)),
+            vec![Diagnostic::Disconnected(2)]
+        );
+        assert_eq!(
+            validate(&ast, Some(Target::Graphflow)),
+            vec![
+                Diagnostic::Disconnected(2),
+                Diagnostic::IsolatedVertex(Target::Graphflow, 3),
+                Diagnostic::UnsupportedExpr(UnsupportedExpr::new(
+                    "graphflow",
+                    ast.constraint().unwrap()
+                )),
+            ]
+        );
+    }
+}